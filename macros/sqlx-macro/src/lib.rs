@@ -1,14 +1,19 @@
 use proc_macro::TokenStream;
 use proc_macro_error::{abort, proc_macro_error};
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{
-    Attribute, Data, DeriveInput, Fields, Ident, Lit, LitStr, Meta, MetaNameValue, Result as SynResult, Token,
+    Attribute, Data, DeriveInput, Fields, Ident, LitStr, Result as SynResult, Token,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     token::Comma,
 };
 
+mod pg_composite;
+mod pg_enum;
+mod pg_jsonb;
+mod verify;
+
 #[derive(Default, Clone)]
 struct ModelCfg {
     schema: String,
@@ -16,6 +21,59 @@ struct ModelCfg {
     pk_cols: Vec<String>,
     insert_skip: Vec<String>,
     skip_update: Vec<String>,
+    on_conflict: Option<Vec<String>>,
+    conflict_update: Option<Vec<String>>,
+    soft_delete: Option<String>,
+    version: Option<String>,
+    tenant: Option<String>,
+    /// Quoted audit table.
+    audit: Option<String>,
+    /// `BulkInsertable` is opt-in: it binds every field as an array, which not all types support.
+    bulk: Option<proc_macro2::Span>,
+    verify: Option<proc_macro2::Span>,
+    belongs_to: Vec<Relation>,
+    has_many: Vec<Relation>,
+}
+
+/// `belongs_to(User, fk = "user_id")` / `has_many(Integration, fk = "user_id", name = "...")`.
+#[derive(Clone)]
+struct Relation {
+    target: syn::Path,
+    /// The foreign key of the child struct, by field or (renamed) column name.
+    fk: LitStr,
+    name: Option<LitStr>,
+}
+
+impl Parse for Relation {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        let target: syn::Path = input.parse()?;
+        let mut fk = None;
+        let mut name = None;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let val: LitStr = input.parse()?;
+            match key.to_string().as_str() {
+                "fk" => fk = Some(val),
+                "name" => name = Some(val),
+                _ => return Err(syn::Error::new(key.span(), "expected fk = \"...\" or name = \"...\"")),
+            }
+        }
+        let Some(fk) = fk else {
+            return Err(syn::Error::new(target.span(), "missing fk = \"...\""));
+        };
+        Ok(Self { target, fk, name })
+    }
+}
+
+impl Relation {
+    fn target_name(&self) -> String {
+        self.target.segments.last().map(|s| s.ident.to_string()).unwrap_or_default()
+    }
 }
 
 impl ModelCfg {
@@ -26,31 +84,149 @@ impl ModelCfg {
             pk_cols: vec!["id".into()],
             insert_skip: vec![],
             skip_update: vec!["id".into(), "created_at".into()],
+            on_conflict: None,
+            conflict_update: None,
+            soft_delete: None,
+            version: None,
+            tenant: None,
+            audit: None,
+            bulk: None,
+            verify: None,
+            belongs_to: vec![],
+            has_many: vec![],
+        }
+    }
+
+    fn qual_table(&self) -> String {
+        format!("\"{}\".\"{}\"", self.schema, self.table)
+    }
+
+    fn audit_table_tokens(&self) -> proc_macro2::TokenStream {
+        match &self.audit {
+            Some(t) => quote! { Some(#t) },
+            None => quote! { None },
         }
     }
 }
 
+/// Where `old_values` of an audit row come from.
+enum AuditOld<'a> {
+    /// Inserts.
+    None,
+    /// The row returned by a `DELETE`.
+    Returned,
+    /// The row selected by this `WHERE` clause from the statement's snapshot. It can't be locked:
+    /// a `FOR UPDATE` in the same statement makes the `UPDATE` skip the row.
+    Select(&'a str),
+    /// An upsert on these conflict columns, which updated the row selected by the `WHERE` clause
+    /// if there is one and inserted it otherwise. Without a clause every row counts as inserted.
+    Upsert(Option<&'a str>, &'a [&'a str]),
+}
+
+/// Wraps the DML `stmt` (without `RETURNING`) of an `#[table(audit)]` type into a single statement
+/// that also writes an audit row per changed row; with `returning` it still yields the changed rows.
+fn audited(cfg: &ModelCfg, audit_table: &str, op: &str, cols_sql: &[String], stmt: &str, old: AuditOld, returning: bool) -> String {
+    let cols = cols_sql.join(", ");
+    let pk_cols_sql = cfg.pk_cols.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>();
+    let pk_obj = cfg
+        .pk_cols
+        .iter()
+        .map(|c| format!("'{}', \"__new\".\"{}\"", c.replace('\'', "''"), c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut ctes = Vec::new();
+    let mut op_sql = format!("'{}'", op);
+    let (old_values, join) = match old {
+        AuditOld::None | AuditOld::Upsert(None, _) => ("NULL::jsonb", String::new()),
+        AuditOld::Returned => ("to_jsonb(\"__new\")", String::new()),
+        AuditOld::Select(where_s) => {
+            ctes.push(format!("\"__old\" AS (SELECT {} FROM {} WHERE {})", cols, cfg.qual_table(), where_s));
+            ("to_jsonb(\"__old\")", format!(" JOIN \"__old\" USING ({})", pk_cols_sql.join(", ")))
+        }
+        AuditOld::Upsert(Some(where_s), conflict) => {
+            ctes.push(format!("\"__old\" AS (SELECT {} FROM {} WHERE {})", cols, cfg.qual_table(), where_s));
+            op_sql = format!("CASE WHEN \"__old\".{} IS NULL THEN 'INSERT' ELSE 'UPDATE' END", conflict[0]);
+            ("to_jsonb(\"__old\")", format!(" LEFT JOIN \"__old\" USING ({})", conflict.join(", ")))
+        }
+    };
+    let new_values = if op == "DELETE" { "NULL::jsonb" } else { "to_jsonb(\"__new\")" };
+    ctes.push(format!("\"__new\" AS ({} RETURNING {})", stmt, cols));
+    let insert_audit = format!(
+        "INSERT INTO {} (\"table_name\", \"operation\", \"pk\", \"old_values\", \"new_values\", \"actor_id\") SELECT '{}.{}', {}, jsonb_build_object({}), {}, {}, NULLIF(current_setting('shl_sqlx.actor_id', true), '') FROM \"__new\"{}",
+        audit_table,
+        cfg.schema.replace('\'', "''"),
+        cfg.table.replace('\'', "''"),
+        op_sql,
+        pk_obj,
+        old_values,
+        new_values,
+        join
+    );
+    if returning {
+        ctes.push(format!("\"__audit\" AS ({})", insert_audit));
+        format!("WITH {} SELECT {} FROM \"__new\"", ctes.join(", "), cols)
+    } else {
+        format!("WITH {} {}", ctes.join(", "), insert_audit)
+    }
+}
+
 enum TableArg {
     Schema(LitStr),
     Table(LitStr),
     PkList(Punctuated<LitStr, Comma>),
     InsertSkip(Punctuated<LitStr, Comma>),
     SkipUpdate(Punctuated<LitStr, Comma>),
+    OnConflict(Punctuated<LitStr, Comma>),
+    ConflictUpdate(Punctuated<LitStr, Comma>),
+    SoftDelete(LitStr),
+    Version(LitStr),
+    Tenant(LitStr),
+    Audit(Option<LitStr>),
+    Bulk(proc_macro2::Span),
+    Verify(proc_macro2::Span),
+    BelongsTo(Relation),
+    HasMany(Relation),
 }
 
 impl Parse for TableArg {
     fn parse(input: ParseStream) -> SynResult<Self> {
         let key: Ident = input.parse()?;
-        if key == "schema" || key == "table" || key == "pk" {
+        if (key == "schema" || key == "table" || key == "pk" || key == "soft_delete" || key == "version" || key == "tenant") && input.peek(Token![=])
+        {
+            input.parse::<Token![=]>()?;
+            let val: LitStr = input.parse()?;
+            return Ok(match key.to_string().as_str() {
+                "schema" => TableArg::Schema(val),
+                "table" => TableArg::Table(val),
+                "pk" => TableArg::PkList(Punctuated::from_iter([val])),
+                "soft_delete" => TableArg::SoftDelete(val),
+                "version" => TableArg::Version(val),
+                "tenant" => TableArg::Tenant(val),
+                _ => unreachable!(),
+            });
+        }
+        if key == "verify" {
+            return Ok(TableArg::Verify(key.span()));
+        }
+        if key == "bulk" {
+            return Ok(TableArg::Bulk(key.span()));
+        }
+        if key == "audit" {
             if input.peek(Token![=]) {
                 input.parse::<Token![=]>()?;
-                let val: LitStr = input.parse()?;
-                return Ok(match key.to_string().as_str() {
-                    "schema" => TableArg::Schema(val),
-                    "table" => TableArg::Table(val),
-                    _ => unreachable!(),
-                });
+                return Ok(TableArg::Audit(Some(input.parse()?)));
             }
+            return Ok(TableArg::Audit(None));
+        }
+        if key == "belongs_to" || key == "has_many" {
+            let content;
+            syn::parenthesized!(content in input);
+            let rel: Relation = content.parse()?;
+            return Ok(if key == "belongs_to" {
+                TableArg::BelongsTo(rel)
+            } else {
+                TableArg::HasMany(rel)
+            });
         }
         if key == "pk" {
             let content;
@@ -70,15 +246,27 @@ impl Parse for TableArg {
             let list = Punctuated::parse_terminated(&content)?;
             return Ok(TableArg::SkipUpdate(list));
         }
+        if key == "on_conflict" {
+            let content;
+            syn::parenthesized!(content in input);
+            let list = Punctuated::parse_terminated(&content)?;
+            return Ok(TableArg::OnConflict(list));
+        }
+        if key == "conflict_update" {
+            let content;
+            syn::parenthesized!(content in input);
+            let list = Punctuated::parse_terminated(&content)?;
+            return Ok(TableArg::ConflictUpdate(list));
+        }
 
         Err(syn::Error::new(
             key.span(),
-            "Unknown key in #[crud(..)]. Expected: schema=..., table=..., pk(...)/pk=\"...\", insert_skip(...), skip_update(...).",
+            "Unknown key in #[crud(..)]. Expected: schema=..., table=..., pk(...)/pk=\"...\", insert_skip(...), skip_update(...), on_conflict(...), conflict_update(...), soft_delete=..., version=..., tenant=..., audit/audit=..., bulk, verify, belongs_to(...), has_many(...).",
         ))
     }
 }
 
-fn to_snake_plural(name: &str) -> String {
+fn to_snake(name: &str) -> String {
     let mut out = String::new();
     for (i, ch) in name.chars().enumerate() {
         if ch.is_uppercase() {
@@ -92,6 +280,11 @@ fn to_snake_plural(name: &str) -> String {
             out.push(ch);
         }
     }
+    out
+}
+
+fn to_snake_plural(name: &str) -> String {
+    let mut out = to_snake(name);
     if !out.ends_with('s') {
         out.push('s');
     }
@@ -128,11 +321,19 @@ fn parse_model_cfg(attrs: &[Attribute], ty_ident: &Ident) -> SynResult<ModelCfg>
                 TableArg::Schema(s) => cfg.schema = s.value(),
                 TableArg::Table(s) => cfg.table = s.value(),
                 TableArg::PkList(list) => {
-                    let span = list.span();
+                    let span = if list.is_empty() { attr.span() } else { list.span() };
                     cfg.pk_cols = list.into_iter().map(|x| x.value()).collect();
                     if cfg.pk_cols.is_empty() {
                         return Err(syn::Error::new(span, "pk(...) cannot be empty"));
                     }
+                    if let Some(dup) = cfg
+                        .pk_cols
+                        .iter()
+                        .enumerate()
+                        .find_map(|(i, c)| cfg.pk_cols[..i].contains(c).then_some(c))
+                    {
+                        return Err(syn::Error::new(span, format!("duplicate column '{}' in pk(...)", dup)));
+                    }
                 }
                 TableArg::InsertSkip(list) => {
                     cfg.insert_skip = list.into_iter().map(|x| x.value()).collect();
@@ -140,71 +341,136 @@ fn parse_model_cfg(attrs: &[Attribute], ty_ident: &Ident) -> SynResult<ModelCfg>
                 TableArg::SkipUpdate(list) => {
                     cfg.skip_update = list.into_iter().map(|x| x.value()).collect();
                 }
+                TableArg::OnConflict(list) => {
+                    let span = if list.is_empty() { attr.span() } else { list.span() };
+                    let cols: Vec<String> = list.into_iter().map(|x| x.value()).collect();
+                    if cols.is_empty() {
+                        return Err(syn::Error::new(span, "on_conflict(...) cannot be empty"));
+                    }
+                    cfg.on_conflict = Some(cols);
+                }
+                TableArg::ConflictUpdate(list) => {
+                    cfg.conflict_update = Some(list.into_iter().map(|x| x.value()).collect());
+                }
+                TableArg::SoftDelete(s) => cfg.soft_delete = Some(s.value()),
+                TableArg::Version(s) => cfg.version = Some(s.value()),
+                TableArg::Tenant(s) => cfg.tenant = Some(s.value()),
+                TableArg::Audit(s) => {
+                    let table = s.map(|s| s.value()).unwrap_or_else(|| "audit_log".into());
+                    let (schema, table) = table.split_once('.').unwrap_or(("public", &table));
+                    cfg.audit = Some(format!("\"{}\".\"{}\"", schema, table));
+                }
+                TableArg::Bulk(span) => cfg.bulk = Some(span),
+                TableArg::Verify(span) => cfg.verify = Some(span),
+                TableArg::BelongsTo(rel) => cfg.belongs_to.push(rel),
+                TableArg::HasMany(rel) => cfg.has_many.push(rel),
             }
         }
     }
     Ok(cfg)
 }
 
-fn field_rename(attrs: &Vec<Attribute>, fallback: &str) -> String {
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AutoNow {
+    /// `now()` on INSERT, never updated afterwards.
+    Add,
+    /// `now()` on INSERT and on every UPDATE.
+    Update,
+}
+
+struct FieldAttrs {
+    name: String,
+    auto_now: Option<AutoNow>,
+    /// Postgres type used in generated DDL instead of the field's `sqlx::Type`.
+    pg_type: Option<String>,
+    encrypted: bool,
+}
+
+/// Parses field-level `#[table(rename = "...", auto_now_add, auto_now_update, pg_type = "...", encrypted)]`.
+fn field_attrs(attrs: &[Attribute], fallback: &str) -> FieldAttrs {
+    let mut out = FieldAttrs {
+        name: fallback.to_string(),
+        auto_now: None,
+        pg_type: None,
+        encrypted: false,
+    };
     for a in attrs {
         if !a.path().is_ident("table") {
             continue;
         }
-        let parsed = a.parse_args_with(|input: ParseStream| -> syn::Result<Option<String>> {
-            if input.is_empty() {
-                return Ok(None);
-            }
-            let key: syn::Ident = input.parse()?;
-            if key == "rename" {
-                input.parse::<Token![=]>()?;
-                let v: LitStr = input.parse()?;
-                Ok(Some(v.value()))
+        let parsed = a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                out.name = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("auto_now_add") {
+                out.auto_now = Some(AutoNow::Add);
+            } else if meta.path.is_ident("auto_now_update") {
+                out.auto_now = Some(AutoNow::Update);
+            } else if meta.path.is_ident("pg_type") {
+                out.pg_type = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("encrypted") {
+                out.encrypted = true;
             } else {
-                Ok(None)
+                return Err(meta.error("unknown field key; expected rename=..., auto_now_add, auto_now_update, pg_type=..., encrypted"));
             }
+            Ok(())
         });
-        if let Ok(Some(name)) = parsed {
-            return name;
+        if let Err(e) = parsed {
+            abort!(e.span(), e.to_string());
         }
     }
-    fallback.to_string()
+    out
 }
 
-fn pk_ty_tokens(pk_types: &Vec<syn::Type>) -> proc_macro2::TokenStream {
-    match pk_types.len() {
-        1 => {
-            let a = &pk_types[0];
-            quote! { #a }
-        }
-        2 => {
-            let a = &pk_types[0];
-            let b = &pk_types[1];
-            quote! { (#a, #b) }
-        }
-        _ => abort!(proc_macro2::Span::call_site(), "only 1-2 PK columns are supported"),
+fn pk_ty_tokens(pk_types: &[syn::Type]) -> proc_macro2::TokenStream {
+    match pk_types {
+        [a] => quote! { #a },
+        _ => quote! { ( #( #pk_types ),* ) },
     }
 }
 
-fn where_pk(pk_cols_sql: &Vec<String>) -> String {
-    match pk_cols_sql.len() {
-        1 => format!("{} = $1", pk_cols_sql[0]),
-        2 => format!("{} = $1 AND {} = $2", pk_cols_sql[0], pk_cols_sql[1]),
-        _ => unreachable!(),
-    }
+fn where_pk(pk_cols_sql: &[String], first: usize) -> String {
+    pk_cols_sql
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{} = ${}", c, first + i))
+        .collect::<Vec<_>>()
+        .join(" AND ")
 }
 
+/// `$1, $2, ..., $n`.
 fn placeholders(n: usize) -> String {
     (1..=n).map(|i| format!("${}", i)).collect::<Vec<_>>().join(", ")
 }
 
+/// Binds a `TableMeta::Id` value named `id` to `q`, destructuring composite keys.
+fn bind_id(pk_len: usize) -> proc_macro2::TokenStream {
+    if pk_len == 1 {
+        return quote! { q = q.bind(id); };
+    }
+    let parts = (0..pk_len).map(|i| format_ident!("pk{}", i)).collect::<Vec<_>>();
+    quote! {
+        let ( #( #parts ),* ) = id;
+        #( q = q.bind(#parts); )*
+    }
+}
+
 struct ColInfo {
     rs_ident: Ident,
     sql_quoted: String,
     ty: syn::Type,
+    auto_now: Option<AutoNow>,
+    pg_type: Option<String>,
+    encrypted: bool,
+}
+
+impl ColInfo {
+    /// Matches either the Rust field name or the (unquoted) SQL column name.
+    fn is(&self, name: &str) -> bool {
+        self.rs_ident == name || unquote(&self.sql_quoted) == name
+    }
 }
 
-fn collect(input: &DeriveInput, cfg: &ModelCfg) -> (Vec<ColInfo>, Vec<Ident>, Vec<String>, Vec<Ident>, Vec<syn::Type>) {
+fn collect(input: &DeriveInput, cfg: &ModelCfg) -> (Vec<ColInfo>, Vec<String>, Vec<Ident>, Vec<syn::Type>) {
     let ds = match &input.data {
         Data::Struct(ds) => ds,
         _ => abort!(input.span(), "only structs are supported"),
@@ -217,40 +483,76 @@ fn collect(input: &DeriveInput, cfg: &ModelCfg) -> (Vec<ColInfo>, Vec<Ident>, Ve
     let mut cols = Vec::<ColInfo>::new();
     for f in named.iter() {
         let name = f.ident.clone().unwrap();
-        let col = field_rename(&f.attrs, &name.to_string());
+        let attrs = field_attrs(&f.attrs, &name.to_string());
+        if attrs.encrypted && !verify::is_encrypted(&f.ty) {
+            abort!(f.ty.span(), "encrypted fields must be Encrypted<T> or Option<Encrypted<T>>");
+        }
         cols.push(ColInfo {
             rs_ident: name,
-            sql_quoted: format!("\"{}\"", col),
+            sql_quoted: format!("\"{}\"", attrs.name),
             ty: f.ty.clone(),
+            auto_now: attrs.auto_now,
+            pg_type: attrs.pg_type,
+            encrypted: attrs.encrypted,
         });
     }
 
     let mut pk_idents = Vec::<Ident>::new();
     let mut pk_types = Vec::<syn::Type>::new();
     for pk in &cfg.pk_cols {
-        let mut found = None;
-        for f in named.iter() {
-            let nm = f.ident.as_ref().unwrap();
-            let col_name = field_rename(&f.attrs, &nm.to_string());
-            if &nm.to_string() == pk || &col_name == pk {
-                found = Some((nm.clone(), f.ty.clone()));
-                break;
+        match cols.iter().find(|c| c.is(pk)) {
+            Some(c) => {
+                pk_idents.push(c.rs_ident.clone());
+                pk_types.push(c.ty.clone());
             }
+            None => abort!(input.span(), format!("pk field '{}' not found", pk)),
         }
-        if let Some((id, ty)) = found {
-            pk_idents.push(id);
-            pk_types.push(ty);
-        } else {
-            abort!(input.span(), format!("pk field '{}' not found", pk));
+    }
+
+    // ciphertexts change on every write, so they can't identify or version a row
+    let keys = cfg
+        .pk_cols
+        .iter()
+        .chain(cfg.on_conflict.iter().flatten())
+        .chain(&cfg.tenant)
+        .chain(&cfg.version)
+        .chain(&cfg.soft_delete);
+    for key in keys {
+        if let Some(c) = cols.iter().find(|c| c.is(key) && c.encrypted) {
+            abort!(
+                c.rs_ident.span(),
+                format!(
+                    "encrypted field '{}' cannot be used as a key, tenant, version or soft_delete column",
+                    c.rs_ident
+                )
+            );
         }
     }
 
-    let rs_names = cols.iter().map(|c| c.rs_ident.clone()).collect::<Vec<_>>();
     let cols_sql = cols.iter().map(|c| c.sql_quoted.clone()).collect::<Vec<_>>();
 
-    (cols, rs_names, cols_sql, pk_idents, pk_types)
+    (cols, cols_sql, pk_idents, pk_types)
+}
+
+fn tenant_col<'a>(input: &DeriveInput, cfg: &ModelCfg, cols: &'a [ColInfo]) -> Option<&'a ColInfo> {
+    cfg.tenant.as_ref().map(|t| match cols.iter().find(|c| c.is(t)) {
+        Some(c) => c,
+        None => abort!(input.span(), format!("tenant field '{}' not found", t)),
+    })
+}
+
+/// The extra `tenant` parameter and its bind for methods of `#[table(tenant = "...")]` types.
+fn tenant_param(tenant: Option<&ColInfo>) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match tenant {
+        Some(_) => (
+            quote! { tenant: <Self as shl_sqlx::postgres::TenantScoped>::Tenant, },
+            quote! { let q = q.bind(tenant); },
+        ),
+        None => (quote! {}, quote! {}),
+    }
 }
 
+#[proc_macro_error]
 #[proc_macro_derive(Table, attributes(table))]
 pub fn derive_table(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
@@ -261,57 +563,441 @@ pub fn derive_table(input: TokenStream) -> TokenStream {
         Err(e) => return e.into_compile_error().into(),
     };
 
-    let (_cols, _rs_names, cols_sql, pk_idents, pk_types) = collect(&input, &cfg);
-    let qual_table = format!("\"{}\".\"{}\"", cfg.schema, cfg.table);
+    let (cols, cols_sql, pk_idents, pk_types) = collect(&input, &cfg);
+    let qual_table = cfg.qual_table();
+    let verified = cfg.verify.map(|span| verify::verify(span, &cfg.schema, &cfg.table, &cols));
 
     let cols_arr = cols_sql.iter().map(|c| syn::LitStr::new(c, input.span()));
     let pk_cols_sql = cfg.pk_cols.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>();
     let pk_arr = pk_cols_sql.iter().map(|c| syn::LitStr::new(c, input.span()));
 
     let id_ty = pk_ty_tokens(&pk_types);
+    let id_expr = match pk_idents.as_slice() {
+        [pk] => quote! { ::std::clone::Clone::clone(&self.#pk) },
+        _ => quote! { ( #( ::std::clone::Clone::clone(&self.#pk_idents) ),* ) },
+    };
+
+    let soft_delete = cfg.soft_delete.as_ref().map(|sd| match cols.iter().find(|c| c.is(sd)) {
+        Some(c) => c.sql_quoted.clone(),
+        None => abort!(input.span(), format!("soft_delete field '{}' not found", sd)),
+    });
 
-    let select_sql = format!("SELECT {} FROM {} WHERE {}", cols_sql.join(", "), qual_table, where_pk(&pk_cols_sql));
-    let delete_sql = format!("DELETE FROM {} WHERE {}", qual_table, where_pk(&pk_cols_sql));
+    let tenant = tenant_col(&input, &cfg, &cols);
+    if tenant.is_some() && (!cfg.belongs_to.is_empty() || !cfg.has_many.is_empty()) {
+        abort!(
+            input.span(),
+            "belongs_to(...) and has_many(...) are not supported on tenant-scoped tables"
+        );
+    }
+    let (tenant_param, bind_tenant) = tenant_param(tenant);
+    let and_tenant = |cond: String, n: usize| match tenant {
+        Some(t) => format!("{} AND {} = ${}", cond, t.sql_quoted, n),
+        None => cond,
+    };
+
+    let where_id = and_tenant(where_pk(&pk_cols_sql, 1), pk_cols_sql.len() + 1);
+    let select_with_deleted_sql = format!("SELECT {} FROM {} WHERE {}", cols_sql.join(", "), qual_table, where_id);
+    let purge_sql = format!("DELETE FROM {} WHERE {}", qual_table, where_id);
+    let (select_sql, delete_sql) = match &soft_delete {
+        Some(sd) => (
+            format!("{} AND {} IS NULL", select_with_deleted_sql, sd),
+            format!("UPDATE {} SET {} = now() WHERE {} AND {} IS NULL", qual_table, sd, where_id, sd),
+        ),
+        None => (select_with_deleted_sql.clone(), purge_sql.clone()),
+    };
+    let delete_returning_sql = format!("{} RETURNING {}", delete_sql, cols_sql.join(", "));
+    let (delete_sql, delete_returning_sql) = match &cfg.audit {
+        Some(audit) => {
+            let live_where = soft_delete.as_ref().map(|sd| format!("{} AND {} IS NULL", where_id, sd));
+            let old = || match &live_where {
+                Some(w) => AuditOld::Select(w),
+                None => AuditOld::Returned,
+            };
+            (
+                audited(&cfg, audit, "DELETE", &cols_sql, &delete_sql, old(), false),
+                audited(&cfg, audit, "DELETE", &cols_sql, &delete_sql, old(), true),
+            )
+        }
+        None => (delete_sql, delete_returning_sql),
+    };
+    let live = soft_delete.as_ref().map(|sd| format!("{} IS NULL", sd));
+    let where_ids = match pk_cols_sql.as_slice() {
+        [pk] => format!("{} = ANY($1)", pk),
+        _ => format!(
+            "({}) IN (SELECT * FROM UNNEST({}))",
+            pk_cols_sql.join(", "),
+            placeholders(pk_cols_sql.len())
+        ),
+    };
+    let where_ids = and_tenant(where_ids, pk_cols_sql.len() + 1);
+    let and_live = |cond: String| match &live {
+        Some(l) => format!("{} AND {}", cond, l),
+        None => cond,
+    };
+    let select_by_ids_sql = format!("SELECT {} FROM {} WHERE {}", cols_sql.join(", "), qual_table, and_live(where_ids));
+    let exists_sql = format!("SELECT EXISTS (SELECT 1 FROM {} WHERE {})", qual_table, and_live(where_id.clone()));
+    let count_where = match tenant {
+        Some(t) => Some(and_live(format!("{} = $1", t.sql_quoted))),
+        None => live.clone(),
+    };
+    let count_sql = match &count_where {
+        Some(w) => format!("SELECT count(*) FROM {} WHERE {}", qual_table, w),
+        None => format!("SELECT count(*) FROM {}", qual_table),
+    };
+    let select_by_ids_lit = syn::LitStr::new(&select_by_ids_sql, input.span());
+    let exists_lit = syn::LitStr::new(&exists_sql, input.span());
+    let count_lit = syn::LitStr::new(&count_sql, input.span());
+    let bind_ids = match pk_types.len() {
+        1 => quote! { q = q.bind(ids); },
+        n => {
+            let idx = (0..n).map(syn::Index::from);
+            quote! { #( q = q.bind(ids.iter().map(|id| &id.#idx).collect::<Vec<_>>()); )* }
+        }
+    };
     let select_lit = syn::LitStr::new(&select_sql, input.span());
     let delete_lit = syn::LitStr::new(&delete_sql, input.span());
+    let delete_returning_lit = syn::LitStr::new(&delete_returning_sql, input.span());
 
-    let bind_select = if pk_idents.len() == 1 {
-        quote! { let mut q = sqlx::query_as::<_, Self>(Self::SQL_SELECT_BY_PK); q = q.bind(id); }
-    } else {
-        quote! { let (a,b) = id; let mut q = sqlx::query_as::<_, Self>(Self::SQL_SELECT_BY_PK); q = q.bind(a); q = q.bind(b); }
+    let bind_id = bind_id(pk_idents.len());
+    let bind_id = quote! { #bind_id #bind_tenant };
+    let bind_select = quote! { let mut q = sqlx::query_as::<_, Self>(Self::SQL_SELECT_BY_PK); #bind_id };
+    let bind_delete = quote! { let mut q = sqlx::query(Self::SQL_DELETE_BY_PK); #bind_id };
+    let bind_delete_returning = quote! { let mut q = sqlx::query_as::<_, Self>(Self::SQL_DELETE_BY_PK_RETURNING); #bind_id };
+
+    let soft_delete_col = match &soft_delete {
+        Some(sd) => quote! { Some(#sd) },
+        None => quote! { None },
     };
-    let bind_delete = if pk_idents.len() == 1 {
-        quote! { let mut q = sqlx::query(Self::SQL_DELETE_BY_PK); q = q.bind(id); }
-    } else {
-        quote! { let (a,b) = id; let mut q = sqlx::query(Self::SQL_DELETE_BY_PK); q = q.bind(a); q = q.bind(b); }
+    let version_col = match &cfg.version {
+        Some(v) => match cols.iter().find(|c| c.is(v)) {
+            Some(c) => {
+                let v = &c.sql_quoted;
+                quote! { Some(#v) }
+            }
+            None => abort!(input.span(), format!("version field '{}' not found", v)),
+        },
+        None => quote! { None },
+    };
+    let (readable_trait, soft_deletable_trait) = match tenant {
+        Some(_) => (format_ident!("TenantReadable"), format_ident!("TenantSoftDeletable")),
+        None => (format_ident!("Readable"), format_ident!("SoftDeletable")),
+    };
+    let soft_deletable = soft_delete.as_ref().map(|sd| {
+        let deleted_where = format!("{} AND {} IS NOT NULL", where_id, sd);
+        let restore_sql = format!("UPDATE {} SET {} = NULL WHERE {}", qual_table, sd, deleted_where);
+        let (restore_sql, purge_sql) = match &cfg.audit {
+            Some(audit) => (
+                audited(&cfg, audit, "UPDATE", &cols_sql, &restore_sql, AuditOld::Select(&deleted_where), false),
+                audited(&cfg, audit, "DELETE", &cols_sql, &purge_sql, AuditOld::Returned, false),
+            ),
+            None => (restore_sql, purge_sql.clone()),
+        };
+        let restore_lit = syn::LitStr::new(&restore_sql, input.span());
+        let select_with_deleted_lit = syn::LitStr::new(&select_with_deleted_sql, input.span());
+        let purge_lit = syn::LitStr::new(&purge_sql, input.span());
+        quote! {
+            impl shl_sqlx::postgres::#soft_deletable_trait for #ident {
+                const SQL_SELECT_BY_PK_WITH_DELETED: &'static str = #select_with_deleted_lit;
+                const SQL_RESTORE_BY_PK: &'static str = #restore_lit;
+                const SQL_PURGE_BY_PK: &'static str = #purge_lit;
+
+                async fn find_by_id_with_deleted<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<Self, sqlx::Error>
+                where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                    let mut q = sqlx::query_as::<_, Self>(Self::SQL_SELECT_BY_PK_WITH_DELETED);
+                    #bind_id
+                    let row = q.fetch_one(exec).await?;
+                    Ok(row)
+                }
+
+                async fn restore_by_id<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<u64, sqlx::Error>
+                where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                    let mut q = sqlx::query(Self::SQL_RESTORE_BY_PK);
+                    #bind_id
+                    let res = q.execute(exec).await?;
+                    Ok(res.rows_affected())
+                }
+
+                async fn purge_by_id<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<u64, sqlx::Error>
+                where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                    let mut q = sqlx::query(Self::SQL_PURGE_BY_PK);
+                    #bind_id
+                    let res = q.execute(exec).await?;
+                    Ok(res.rows_affected())
+                }
+            }
+        }
+    });
+
+    let column_defs = cols.iter().map(|ci| {
+        let name = syn::LitStr::new(&ci.sql_quoted, input.span());
+        let (_, optional) = verify::strip_option(&ci.ty);
+        let ty = &ci.ty;
+        let type_info = match &ci.pg_type {
+            Some(t) => quote! { || sqlx::postgres::PgTypeInfo::with_name(#t) },
+            None => quote! { <#ty as sqlx::Type<sqlx::Postgres>>::type_info },
+        };
+        let default = match ci.auto_now {
+            Some(_) => quote! { Some("now()") },
+            None => quote! { None },
+        };
+        quote! {
+            shl_sqlx::postgres::ColumnDef {
+                name: #name,
+                type_info: #type_info,
+                nullable: #optional,
+                default: #default,
+            }
+        }
+    });
+
+    let belongs_to = cfg.belongs_to.iter().map(|rel| {
+        let target = &rel.target;
+        let fk = match cols.iter().find(|c| c.is(&rel.fk.value())) {
+            Some(c) => c,
+            None => abort!(rel.fk.span(), format!("fk field '{}' not found", rel.fk.value())),
+        };
+        let fk_ident = &fk.rs_ident;
+        let method = format_ident!("{}", rel.name.as_ref().map(|n| n.value()).unwrap_or_else(|| to_snake(&rel.target_name())));
+        let (_, optional) = verify::strip_option(&fk.ty);
+        let (ret, body) = if optional {
+            (
+                quote! { Option<#target> },
+                quote! {
+                    match &self.#fk_ident {
+                        Some(id) => <#target as shl_sqlx::postgres::Readable>::find_by_id(exec, id.clone()).await.map(Some),
+                        None => Ok(None),
+                    }
+                },
+            )
+        } else {
+            (
+                quote! { #target },
+                quote! { <#target as shl_sqlx::postgres::Readable>::find_by_id(exec, self.#fk_ident.clone()).await },
+            )
+        };
+        quote! {
+            /// Loads the row referenced by this foreign key.
+            pub async fn #method<'e, E>(&self, exec: E) -> Result<#ret, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send + 'e {
+                #body
+            }
+        }
+    });
+    let has_many = cfg.has_many.iter().map(|rel| {
+        let target = &rel.target;
+        // resolved on the target, which knows its own field names and `rename`s
+        let fk = format_ident!("__column_{}", rel.fk.value(), span = rel.fk.span());
+        let pk = match pk_idents.as_slice() {
+            [pk] => pk,
+            _ => abort!(rel.target.span(), "has_many requires a single-column primary key"),
+        };
+        let name = rel
+            .name
+            .as_ref()
+            .map(|n| n.value())
+            .unwrap_or_else(|| to_snake_plural(&rel.target_name()));
+        let method = format_ident!("{}", name);
+        let load = format_ident!("load_{}", name);
+        quote! {
+            /// Loads the rows referencing this one.
+            pub async fn #method<'e, E>(&self, exec: E) -> Result<Vec<#target>, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> {
+                use shl_sqlx::postgres::Queryable;
+                let (fk, _) = #target::#fk();
+                #target::select().filter(fk.eq(self.#pk.clone())).fetch_all(exec).await
+            }
+
+            /// Loads the rows referencing each of `parents` with one query, grouped in the order of `parents`.
+            pub async fn #load<'e, E>(exec: E, parents: &[Self]) -> Result<Vec<Vec<#target>>, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> {
+                let (fk, fk_value) = #target::#fk();
+                shl_sqlx::postgres::relations::load_children(exec, fk, parents.iter().map(|p| p.#pk.clone()), fk_value).await
+            }
+        }
+    });
+    // the loaders have no tenant to scope the target's rows with
+    let untenanted = cfg.belongs_to.iter().chain(&cfg.has_many).map(|rel| {
+        let target = &rel.target;
+        let msg = format!(
+            "belongs_to(...) and has_many(...) can't target the tenant-scoped table {}",
+            rel.target_name()
+        );
+        quote_spanned! { target.span() =>
+            const _: () = ::std::assert!(<#target as shl_sqlx::postgres::TableMeta>::TENANT_COL.is_none(), #msg);
+        }
+    });
+    let relations = (!cfg.belongs_to.is_empty() || !cfg.has_many.is_empty()).then(|| {
+        quote! {
+            #( #untenanted )*
+
+            impl #ident {
+                #( #belongs_to )*
+                #( #has_many )*
+            }
+        }
+    });
+
+    let tenant_col = match tenant {
+        Some(t) => {
+            let col = &t.sql_quoted;
+            quote! { Some(#col) }
+        }
+        None => quote! { None },
+    };
+    let audit_table = cfg.audit_table_tokens();
+    let tenant_scoped = tenant.map(|t| {
+        let ty = &t.ty;
+        quote! {
+            impl shl_sqlx::postgres::TenantScoped for #ident {
+                type Tenant = #ty;
+            }
+        }
+    });
+    let paginated = match tenant {
+        Some(_) => quote! { shl_sqlx::postgres::TenantPaginated },
+        None => quote! { shl_sqlx::postgres::Paginated },
     };
 
+    // `has_many(.., fk = "...")` on another table names a field or a column of this one
+    let column_accessors = cols.iter().flat_map(|c| {
+        let column = unquote(&c.sql_quoted);
+        let alias = (c.rs_ident != column && !cols.iter().any(|o| o.rs_ident == column))
+            .then(|| syn::parse_str::<Ident>(&column).ok())
+            .flatten();
+        std::iter::once(c.rs_ident.clone()).chain(alias).map(move |name| {
+            let accessor = format_ident!("__column_{}", name);
+            let (field, ty, quoted) = (&c.rs_ident, &c.ty, &c.sql_quoted);
+            quote! {
+                #[doc(hidden)]
+                pub fn #accessor() -> (shl_sqlx::postgres::query::Column<Self, #ty>, fn(&Self) -> &#ty) {
+                    (shl_sqlx::postgres::query::Column::new(#quoted), |row| &row.#field)
+                }
+            }
+        })
+    });
+
+    let vis = &input.vis;
+    let columns_ident = format_ident!("{}Columns", ident);
+    let col_fields = cols.iter().map(|c| &c.rs_ident).collect::<Vec<_>>();
+    let col_types = cols.iter().map(|c| &c.ty);
+    let col_names = cols.iter().map(|c| syn::LitStr::new(&c.sql_quoted, input.span()));
+
     let expanded = quote! {
+        #[derive(Clone, Copy)]
+        #vis struct #columns_ident {
+            #( pub #col_fields: shl_sqlx::postgres::query::Column<#ident, #col_types>, )*
+        }
+
+        impl shl_sqlx::postgres::query::Queryable for #ident {
+            type Columns = #columns_ident;
+
+            fn cols() -> Self::Columns {
+                #columns_ident {
+                    #( #col_fields: shl_sqlx::postgres::query::Column::new(#col_names), )*
+                }
+            }
+        }
+
         impl shl_sqlx::postgres::TableMeta for #ident {
             type Id = #id_ty;
             const QUAL_TABLE: &'static str = #qual_table;
             const COLS: &'static [&'static str] = &[ #( #cols_arr ),* ];
             const PK_COLS: &'static [&'static str] = &[ #( #pk_arr ),* ];
+            const SOFT_DELETE_COL: Option<&'static str> = #soft_delete_col;
+            const TENANT_COL: Option<&'static str> = #tenant_col;
+            const VERSION_COL: Option<&'static str> = #version_col;
+            const AUDIT_TABLE: Option<&'static str> = #audit_table;
+            const COLUMN_DEFS: &'static [shl_sqlx::postgres::ColumnDef] = &[ #( #column_defs ),* ];
         }
 
-        impl shl_sqlx::postgres::Readable for #ident {
+        // Only implemented for `Clone` keys; the lifetime defers the bound to the use site, so
+        // the derive still compiles for others.
+        impl shl_sqlx::postgres::HasId for #ident
+        where #( for<'__id> #pk_types: ::std::clone::Clone ),*
+        {
+            fn id(&self) -> Self::Id {
+                #id_expr
+            }
+        }
+
+        #tenant_scoped
+
+        // bounded like `HasId`, as `Table` doesn't require `FromRow`
+        impl #paginated for #ident
+        where for<'__r> #ident: sqlx::FromRow<'__r, sqlx::postgres::PgRow> + ::std::marker::Send + ::std::marker::Unpin
+        {}
+
+        impl #ident {
+            #( #column_accessors )*
+        }
+
+        impl shl_sqlx::postgres::#readable_trait for #ident {
             const SQL_SELECT_BY_PK: &'static str = #select_lit;
             const SQL_DELETE_BY_PK: &'static str = #delete_lit;
+            const SQL_DELETE_BY_PK_RETURNING: &'static str = #delete_returning_lit;
+            const SQL_SELECT_BY_PKS: &'static str = #select_by_ids_lit;
+            const SQL_EXISTS_BY_PK: &'static str = #exists_lit;
+            const SQL_COUNT: &'static str = #count_lit;
 
-            async fn find_by_id<'e, E>(exec: E, id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<Self, sqlx::Error>
+            async fn find_by_id<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<Self, sqlx::Error>
             where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
                 #bind_select
                 let row = q.fetch_one(exec).await?;
                 Ok(row)
             }
 
-            async fn delete_by_id<'e, E>(exec: E, id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<u64, sqlx::Error>
+            async fn find_optional_by_id<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<Option<Self>, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                #bind_select
+                let row = q.fetch_optional(exec).await?;
+                Ok(row)
+            }
+
+            async fn find_by_ids<'e, E>(exec: E, #tenant_param ids: &'e [<Self as shl_sqlx::postgres::TableMeta>::Id]) -> Result<Vec<Self>, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                let mut q = sqlx::query_as::<_, Self>(Self::SQL_SELECT_BY_PKS);
+                #bind_ids
+                #bind_tenant
+                let rows = q.fetch_all(exec).await?;
+                Ok(rows)
+            }
+
+            async fn exists_by_id<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<bool, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                let mut q = sqlx::query_scalar::<_, bool>(Self::SQL_EXISTS_BY_PK);
+                #bind_id
+                let exists = q.fetch_one(exec).await?;
+                Ok(exists)
+            }
+
+            async fn count<'e, E>(exec: E, #tenant_param) -> Result<i64, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                let q = sqlx::query_scalar::<_, i64>(Self::SQL_COUNT);
+                #bind_tenant
+                let count = q.fetch_one(exec).await?;
+                Ok(count)
+            }
+
+            async fn delete_by_id<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<u64, sqlx::Error>
             where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
                 #bind_delete
                 let res = q.execute(exec).await?;
                 Ok(res.rows_affected())
             }
+
+            async fn delete_returning<'e, E>(exec: E, #tenant_param id: <Self as shl_sqlx::postgres::TableMeta>::Id) -> Result<Self, sqlx::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                #bind_delete_returning
+                let row = q.fetch_one(exec).await?;
+                Ok(row)
+            }
         }
+
+        #soft_deletable
+
+        #relations
+
+        #verified
     };
     expanded.into()
 }
@@ -327,46 +1013,310 @@ pub fn derive_insertable(input: TokenStream) -> TokenStream {
         Err(e) => return e.into_compile_error().into(),
     };
 
-    let (cols, _rs_names, cols_sql, _pk_idents, _pk_types) = collect(&input, &cfg);
+    let (cols, cols_sql, _pk_idents, _pk_types) = collect(&input, &cfg);
+    let tenant = tenant_col(&input, &cfg, &cols);
+    let is_tenant = |ci: &ColInfo| tenant.is_some_and(|t| t.rs_ident == ci.rs_ident);
+    let (tenant_param, _) = tenant_param(tenant);
 
-    let insert_cols: Vec<_> = cols_sql
+    let insert_ci: Vec<&ColInfo> = cols
         .iter()
-        .filter(|c| !cfg.insert_skip.iter().any(|s| s == &unquote(c)))
-        .cloned()
+        .filter(|ci| !cfg.insert_skip.iter().any(|s| s == &unquote(&ci.sql_quoted)))
         .collect();
-    let insert_fields: Vec<Ident> = cols
+    if let Some(t) = tenant
+        && !insert_ci.iter().any(|ci| is_tenant(ci))
+    {
+        abort!(input.span(), format!("tenant field '{}' cannot be in insert_skip", t.rs_ident));
+    }
+    let insert_cols: Vec<String> = insert_ci.iter().map(|ci| ci.sql_quoted.clone()).collect();
+    // Fields bound as one array each by `insert_many`; the tenant is bound once.
+    let insert_fields: Vec<Ident> = insert_ci
         .iter()
-        .filter(|ci| !cfg.insert_skip.iter().any(|s| s == &unquote(&ci.sql_quoted)))
+        .filter(|ci| ci.auto_now.is_none() && !is_tenant(ci))
         .map(|ci| ci.rs_ident.clone())
         .collect();
+    let mut bind_no = 0;
+    let insert_values = insert_ci
+        .iter()
+        .map(|ci| match ci.auto_now {
+            Some(_) => "now()".to_string(),
+            None => {
+                bind_no += 1;
+                format!("${}", bind_no)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
 
-    let qual_table = format!("\"{}\".\"{}\"", cfg.schema, cfg.table);
+    let qual_table = cfg.qual_table();
     let sql_insert = if insert_cols.is_empty() {
         format!("INSERT INTO {} DEFAULT VALUES", qual_table)
     } else {
-        format!(
-            "INSERT INTO {} ({} ) VALUES ({})",
+        format!("INSERT INTO {} ({} ) VALUES ({})", qual_table, insert_cols.join(", "), insert_values)
+    };
+    let sql_insert_audited = match &cfg.audit {
+        Some(audit) => audited(&cfg, audit, "INSERT", &cols_sql, &sql_insert, AuditOld::None, false),
+        None => sql_insert.clone(),
+    };
+    let sql_insert_lit = syn::LitStr::new(&sql_insert_audited, input.span());
+    let bound_ci = insert_ci.iter().filter(|ci| ci.auto_now.is_none() && !is_tenant(ci)).collect::<Vec<_>>();
+    let sql_insert_many = if bound_ci.is_empty() {
+        let sql_insert_lit = syn::LitStr::new(&sql_insert, input.span());
+        quote! { #sql_insert_lit }
+    } else {
+        let tenant_placeholder = format!("${}", bound_ci.len() + 1);
+        let select_list = insert_ci
+            .iter()
+            .map(|ci| match ci.auto_now {
+                Some(_) => "now()",
+                None if is_tenant(ci) => tenant_placeholder.as_str(),
+                None => ci.sql_quoted.as_str(),
+            })
+            .collect::<Vec<_>>();
+        let prefix = format!(
+            "INSERT INTO {} ({}) SELECT {} FROM UNNEST(",
             qual_table,
             insert_cols.join(", "),
-            placeholders(insert_cols.len())
-        )
+            select_list.join(", ")
+        );
+        let suffix = format!(
+            ") AS \"__rows\"({})",
+            bound_ci.iter().map(|ci| ci.sql_quoted.as_str()).collect::<Vec<_>>().join(", ")
+        );
+        let (prefix, suffix) = (syn::LitStr::new(&prefix, input.span()), syn::LitStr::new(&suffix, input.span()));
+        // positions in `COLUMN_DEFS`, which follows the field order
+        let def_idx = bound_ci.iter().map(|b| cols.iter().position(|c| c.rs_ident == b.rs_ident).unwrap());
+        quote! {
+            static SQL: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+            SQL.get_or_init(|| {
+                let defs = <Self as shl_sqlx::postgres::TableMeta>::COLUMN_DEFS;
+                format!("{}{}{}", #prefix, shl_sqlx::postgres::unnest_params([ #( &defs[#def_idx] ),* ]), #suffix)
+            })
+        }
+    };
+    let copy_fields = insert_ci.iter().map(|ci| &ci.rs_ident).collect::<Vec<_>>();
+    let bind_tenant_many = tenant.map(|_| quote! { let q = q.bind(tenant.clone()); });
+    let insert_many_chunk = if insert_fields.is_empty() {
+        quote! {
+            for _ in chunk {
+                let q = sqlx::query(Self::sql_insert_many());
+                #bind_tenant_many
+                affected += q.execute(&mut *tx).await?.rows_affected();
+            }
+        }
+    } else {
+        quote! {
+            let mut q = sqlx::query(Self::sql_insert_many());
+            #( q = q.bind(chunk.iter().map(|row| &row.#insert_fields).collect::<Vec<_>>()); )*
+            #bind_tenant_many
+            affected += q.execute(&mut *tx).await?.rows_affected();
+        }
+    };
+    let sql_insert_returning = match &cfg.audit {
+        Some(audit) => audited(&cfg, audit, "INSERT", &cols_sql, &sql_insert, AuditOld::None, true),
+        None => format!("{} RETURNING {}", sql_insert, cols_sql.join(", ")),
     };
-    let sql_insert_lit = syn::LitStr::new(&sql_insert, input.span());
+    let sql_insert_returning_lit = syn::LitStr::new(&sql_insert_returning, input.span());
 
     let insert_cols_arr = insert_cols.iter().map(|c| syn::LitStr::new(c, input.span()));
-    let bind_fields = insert_fields.iter().map(|f| quote! { q = q.bind(&self.#f); });
+    let bind_fields: Vec<_> = insert_ci
+        .iter()
+        .filter(|ci| ci.auto_now.is_none())
+        .map(|ci| match is_tenant(ci) {
+            true => quote! { q = q.bind(tenant); },
+            false => {
+                let f = &ci.rs_ident;
+                quote! { q = q.bind(&self.#f); }
+            }
+        })
+        .collect();
 
-    let expanded = quote! {
-        impl shl_sqlx::postgres::Insertable for #ident {
-            const INSERT_COLS: &'static [&'static str] = &[ #( #insert_cols_arr ),* ];
-            const SQL_INSERT: &'static str = #sql_insert_lit;
+    let find_col = |name: &String| match cols.iter().find(|c| c.is(name)) {
+        Some(c) => c,
+        None => abort!(input.span(), format!("conflict column '{}' not found", name)),
+    };
+    let version_col = cfg.version.as_ref().map(|v| match cols.iter().find(|c| c.is(v)) {
+        Some(c) => c,
+        None => abort!(input.span(), format!("version field '{}' not found", v)),
+    });
+    let conflict_cols: Vec<&ColInfo> = cfg.on_conflict.as_ref().unwrap_or(&cfg.pk_cols).iter().map(find_col).collect();
+    let conflict_update: Vec<&ColInfo> = match &cfg.conflict_update {
+        Some(list) => list.iter().map(find_col).collect(),
+        None => cols
+            .iter()
+            .filter(|ci| insert_cols.contains(&ci.sql_quoted))
+            .filter(|ci| ci.auto_now != Some(AutoNow::Add))
+            .filter(|ci| !cfg.skip_update.iter().any(|s| ci.is(s)))
+            .filter(|ci| !conflict_cols.iter().any(|cc| cc.rs_ident == ci.rs_ident))
+            .filter(|ci| version_col.is_none_or(|v| v.rs_ident != ci.rs_ident))
+            .filter(|ci| !is_tenant(ci))
+            .collect(),
+    };
 
-            async fn insert<'e, E>(&self, exec: E) -> Result<u64, sqlx::Error>
-            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
-                let mut q = sqlx::query(Self::SQL_INSERT);
-                #( #bind_fields )*
-                let res = q.execute(exec).await?;
-                Ok(res.rows_affected())
+    let conflict_target = conflict_cols.iter().map(|c| c.sql_quoted.as_str()).collect::<Vec<_>>().join(", ");
+    let sql_insert_or_ignore = format!("{} ON CONFLICT ({}) DO NOTHING", sql_insert, conflict_target);
+    let sql_upsert = if conflict_update.is_empty() {
+        sql_insert_or_ignore.clone()
+    } else {
+        let mut set_list = conflict_update
+            .iter()
+            .map(|c| format!("{0} = EXCLUDED.{0}", c.sql_quoted))
+            .collect::<Vec<_>>();
+        if let Some(v) = version_col {
+            set_list.push(format!("{0} = {1}.{0} + 1", v.sql_quoted, qual_table));
+        }
+        let set_list = set_list.join(", ");
+        let sql = format!("{} ON CONFLICT ({}) DO UPDATE SET {}", sql_insert, conflict_target, set_list);
+        match tenant {
+            Some(t) => format!("{0} WHERE {1}.{2} = EXCLUDED.{2}", sql, qual_table, t.sql_quoted),
+            None => sql,
+        }
+    };
+    let (sql_upsert, sql_insert_or_ignore) = match &cfg.audit {
+        Some(audit) => {
+            // the row an upsert overwrites has the inserted values in the conflict columns
+            let bound = insert_ci.iter().filter(|ci| ci.auto_now.is_none()).collect::<Vec<_>>();
+            let old_where = conflict_cols
+                .iter()
+                .map(|cc| {
+                    let n = bound.iter().position(|b| b.rs_ident == cc.rs_ident)?;
+                    Some(format!("{} = ${}", cc.sql_quoted, n + 1))
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|conds| conds.join(" AND "));
+            let conflict = conflict_cols.iter().map(|c| c.sql_quoted.as_str()).collect::<Vec<_>>();
+            let old = AuditOld::Upsert(old_where.as_deref(), &conflict);
+            (
+                audited(&cfg, audit, "INSERT", &cols_sql, &sql_upsert, old, false),
+                audited(&cfg, audit, "INSERT", &cols_sql, &sql_insert_or_ignore, AuditOld::None, false),
+            )
+        }
+        None => (sql_upsert, sql_insert_or_ignore),
+    };
+    let sql_upsert_lit = syn::LitStr::new(&sql_upsert, input.span());
+    let sql_insert_or_ignore_lit = syn::LitStr::new(&sql_insert_or_ignore, input.span());
+    let conflict_cols_arr = conflict_cols.iter().map(|c| syn::LitStr::new(&c.sql_quoted, input.span()));
+    let conflict_update_arr = conflict_update.iter().map(|c| syn::LitStr::new(&c.sql_quoted, input.span()));
+
+    let insert_consts = quote! {
+        const INSERT_COLS: &'static [&'static str] = &[ #( #insert_cols_arr ),* ];
+        const SQL_INSERT: &'static str = #sql_insert_lit;
+        const SQL_INSERT_RETURNING: &'static str = #sql_insert_returning_lit;
+    };
+    let insert_methods = quote! {
+        async fn insert<'e, E>(&self, exec: E, #tenant_param) -> Result<u64, sqlx::Error>
+        where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+            let mut q = sqlx::query(Self::SQL_INSERT);
+            #( #bind_fields )*
+            let res = q.execute(exec).await?;
+            Ok(res.rows_affected())
+        }
+
+        async fn insert_returning<'e, E>(&self, exec: E, #tenant_param) -> Result<Self, sqlx::Error>
+        where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+            let mut q = sqlx::query_as::<_, Self>(Self::SQL_INSERT_RETURNING);
+            #( #bind_fields )*
+            let row = q.fetch_one(exec).await?;
+            Ok(row)
+        }
+
+    };
+    let bulk_methods = quote! {
+        fn sql_insert_many() -> &'static str {
+            #sql_insert_many
+        }
+
+        async fn insert_many<'a, A>(conn: A, #tenant_param rows: &'a [Self]) -> Result<u64, sqlx::Error>
+        where A: sqlx::Acquire<'a, Database = sqlx::Postgres> + Send {
+            if rows.is_empty() {
+                return Ok(0);
+            }
+            let mut tx = conn.begin().await?;
+            let mut affected = 0;
+            for chunk in rows.chunks(Self::INSERT_MANY_CHUNK.max(1)) {
+                #insert_many_chunk
+            }
+            tx.commit().await?;
+            Ok(affected)
+        }
+    };
+    let upsert_consts = quote! {
+        const CONFLICT_COLS: &'static [&'static str] = &[ #( #conflict_cols_arr ),* ];
+        const CONFLICT_UPDATE_COLS: &'static [&'static str] = &[ #( #conflict_update_arr ),* ];
+        const SQL_UPSERT: &'static str = #sql_upsert_lit;
+        const SQL_INSERT_OR_IGNORE: &'static str = #sql_insert_or_ignore_lit;
+    };
+    let upsert_methods = quote! {
+        async fn upsert<'e, E>(&self, exec: E, #tenant_param) -> Result<u64, sqlx::Error>
+        where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+            let mut q = sqlx::query(Self::SQL_UPSERT);
+            #( #bind_fields )*
+            let res = q.execute(exec).await?;
+            Ok(res.rows_affected())
+        }
+
+        async fn insert_or_ignore<'e, E>(&self, exec: E, #tenant_param) -> Result<u64, sqlx::Error>
+        where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+            let mut q = sqlx::query(Self::SQL_INSERT_OR_IGNORE);
+            #( #bind_fields )*
+            let res = q.execute(exec).await?;
+            Ok(res.rows_affected())
+        }
+    };
+
+    // bulk paths can't write audit rows, so audited types don't get them at all
+    if let Some(span) = cfg.bulk
+        && cfg.audit.is_some()
+    {
+        abort!(
+            span,
+            "bulk is not supported on #[table(audit)] types: insert_many and copy_in can't write audit rows"
+        );
+    }
+    let expanded = match tenant {
+        Some(_) => {
+            let bulk = cfg.bulk.map(|_| {
+                quote! {
+                    impl shl_sqlx::postgres::TenantBulkInsertable for #ident {
+                        #bulk_methods
+                    }
+                }
+            });
+            quote! {
+                impl shl_sqlx::postgres::TenantInsertable for #ident {
+                    #insert_consts
+                    #upsert_consts
+                    #insert_methods
+                    #upsert_methods
+                }
+
+                #bulk
+            }
+        }
+        None => {
+            let bulk = cfg.bulk.map(|_| {
+                quote! {
+                    impl shl_sqlx::postgres::BulkInsertable for #ident {
+                        #bulk_methods
+
+                        fn encode_copy_row(&self, row: &mut shl_sqlx::postgres::copy::CopyRow) -> Result<(), sqlx::error::BoxDynError> {
+                            #( row.push(&self.#copy_fields)?; )*
+                            Ok(())
+                        }
+                    }
+                }
+            });
+            quote! {
+                impl shl_sqlx::postgres::Insertable for #ident {
+                    #insert_consts
+                    #insert_methods
+                }
+
+                #bulk
+
+                impl shl_sqlx::postgres::Upsertable for #ident {
+                    #upsert_consts
+                    #upsert_methods
+                }
             }
         }
     };
@@ -383,70 +1333,269 @@ pub fn derive_updatable(input: TokenStream) -> TokenStream {
         Ok(c) => c,
         Err(e) => return e.into_compile_error().into(),
     };
-    let (cols, _rs_names, cols_sql, pk_idents, _pk_types) = collect(&input, &cfg);
+    let (cols, cols_sql, pk_idents, pk_types) = collect(&input, &cfg);
+
+    let version_col = cfg.version.as_ref().map(|v| match cols.iter().find(|c| c.is(v)) {
+        Some(c) => c,
+        None => abort!(input.span(), format!("version field '{}' not found", v)),
+    });
+
+    let tenant = tenant_col(&input, &cfg, &cols);
+    let (tenant_param, bind_tenant) = tenant_param(tenant);
 
     let upd_cols: Vec<&ColInfo> = cols
         .iter()
-        .filter(|ci| {
-            !cfg.skip_update
-                .iter()
-                .any(|s| s == &ci.rs_ident.to_string() || s == &unquote(&ci.sql_quoted))
-        })
+        .filter(|ci| !cfg.skip_update.iter().any(|s| ci.is(s)))
+        .filter(|ci| ci.auto_now.is_none())
+        .filter(|ci| version_col.is_none_or(|v| v.rs_ident != ci.rs_ident))
+        .filter(|ci| tenant.is_none_or(|t| t.rs_ident != ci.rs_ident))
         .collect();
 
     if upd_cols.is_empty() {
         abort!(input.span(), "no fields to UPDATE (all are in skip_update)");
     }
 
-    let set_list: Vec<String> = upd_cols
+    let mut set_list: Vec<String> = upd_cols
         .iter()
         .enumerate()
         .map(|(i, ci)| format!("{} = ${}", ci.sql_quoted, i + 1))
         .collect();
+    set_list.extend(
+        cols.iter()
+            .filter(|ci| ci.auto_now == Some(AutoNow::Update))
+            .map(|ci| format!("{} = now()", ci.sql_quoted)),
+    );
 
-    let qual_table = format!("\"{}\".\"{}\"", cfg.schema, cfg.table);
+    let qual_table = cfg.qual_table();
 
-    let mut where_s = String::new();
-    for (i, pk) in cfg.pk_cols.iter().enumerate() {
-        if i > 0 {
-            where_s.push_str(" AND ");
-        }
-        where_s.push_str(&format!("\"{}\" = ${}", pk, i + upd_cols.len() + 1));
+    let pk_cols_sql = cfg.pk_cols.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>();
+    let mut where_s = where_pk(&pk_cols_sql, upd_cols.len() + 1);
+    if let Some(v) = version_col {
+        set_list.push(format!("{0} = {0} + 1", v.sql_quoted));
+        where_s.push_str(&format!(" AND {} = ${}", v.sql_quoted, upd_cols.len() + pk_cols_sql.len() + 1));
+    }
+    if let Some(t) = tenant {
+        let n = upd_cols.len() + pk_cols_sql.len() + usize::from(version_col.is_some()) + 1;
+        where_s.push_str(&format!(" AND {} = ${}", t.sql_quoted, n));
     }
     let sql_update = format!("UPDATE {} SET {} WHERE {}", qual_table, set_list.join(", "), where_s);
+    let (sql_update, sql_update_returning) = match &cfg.audit {
+        Some(audit) => (
+            audited(&cfg, audit, "UPDATE", &cols_sql, &sql_update, AuditOld::Select(&where_s), false),
+            audited(&cfg, audit, "UPDATE", &cols_sql, &sql_update, AuditOld::Select(&where_s), true),
+        ),
+        None => {
+            let returning = format!("{} RETURNING {}", sql_update, cols_sql.join(", "));
+            (sql_update, returning)
+        }
+    };
     let sql_update_lit = syn::LitStr::new(&sql_update, input.span());
+    let sql_update_returning_lit = syn::LitStr::new(&sql_update_returning, input.span());
 
-    let bind_upd = upd_cols.iter().map(|ci| {
-        let id = ci.rs_ident.clone();
-        quote! { q = q.bind(&self.#id); }
-    });
+    let bind_upd: Vec<_> = upd_cols
+        .iter()
+        .map(|ci| {
+            let id = ci.rs_ident.clone();
+            quote! { q = q.bind(&self.#id); }
+        })
+        .collect();
+
+    let bind_pk = match version_col {
+        Some(v) => {
+            let v = &v.rs_ident;
+            quote! { #( q = q.bind(&self.#pk_idents); )* q = q.bind(&self.#v); #bind_tenant }
+        }
+        None => quote! { #( q = q.bind(&self.#pk_idents); )* #bind_tenant },
+    };
 
-    let bind_pk = match pk_idents.len() {
-        1 => {
-            let a = pk_idents[0].clone();
-            quote! { q = q.bind(&self.#a); }
+    let (version_ty, patch_version, patch_check) = match version_col {
+        Some(v) => {
+            let ty = &v.ty;
+            (
+                quote! { #ty },
+                quote! {
+                    let stale = shl_sqlx::postgres::StaleVersion {
+                        table: <Self as shl_sqlx::postgres::TableMeta>::QUAL_TABLE,
+                        version: i64::from(version),
+                    };
+                    q = q.bind(version);
+                },
+                quote! {
+                    if res.rows_affected() == 0 {
+                        return Err(stale.into());
+                    }
+                },
+            )
         }
-        2 => {
-            let a = pk_idents[0].clone();
-            let b = pk_idents[1].clone();
-            quote! { q = q.bind(&self.#a); q = q.bind(&self.#b); }
+        None => (quote! { () }, quote! { let () = version; }, quote! {}),
+    };
+
+    let (error_ty, check_affected, fetch_updated) = match version_col {
+        Some(v) => {
+            let v = &v.rs_ident;
+            let stale = quote! {
+                shl_sqlx::postgres::StaleVersion {
+                    table: <Self as shl_sqlx::postgres::TableMeta>::QUAL_TABLE,
+                    version: i64::from(self.#v),
+                }
+            };
+            (
+                quote! { shl_sqlx::postgres::UpdateError },
+                quote! {
+                    if res.rows_affected() == 0 {
+                        return Err(#stale.into());
+                    }
+                },
+                quote! { q.fetch_optional(exec).await?.ok_or_else(|| #stale)? },
+            )
         }
-        _ => abort!(input.span(), "only 1-2 PK columns are supported"),
+        None => (quote! { sqlx::Error }, quote! {}, quote! { q.fetch_one(exec).await? }),
+    };
+
+    let vis = &input.vis;
+    let patch_ident = format_ident!("{}Patch", ident);
+    let patch_fields = upd_cols.iter().map(|ci| &ci.rs_ident).collect::<Vec<_>>();
+    let patch_types = upd_cols.iter().map(|ci| &ci.ty);
+    let patch_sets = upd_cols
+        .iter()
+        .map(|ci| syn::LitStr::new(&format!("{} = $", ci.sql_quoted), input.span()));
+    let patch_touch = cols
+        .iter()
+        .filter(|ci| ci.auto_now == Some(AutoNow::Update))
+        .map(|ci| format!("{} = now()", ci.sql_quoted))
+        .chain(version_col.map(|v| format!("{0} = {0} + 1", v.sql_quoted)))
+        .map(|s| syn::LitStr::new(&s, input.span()));
+    // the key, version and tenant come first, so the SET list is the only part built at runtime
+    let mut patch_where = where_pk(&pk_cols_sql, 1);
+    let mut patch_keys = pk_cols_sql.len();
+    for c in version_col.into_iter().chain(tenant) {
+        patch_keys += 1;
+        patch_where.push_str(&format!(" AND {} = ${}", c.sql_quoted, patch_keys));
+    }
+    let sql_patch = format!("UPDATE {} SET \0 WHERE {}", qual_table, patch_where);
+    let sql_patch = match &cfg.audit {
+        Some(audit) => audited(&cfg, audit, "UPDATE", &cols_sql, &sql_patch, AuditOld::Select(&patch_where), false),
+        None => sql_patch,
+    };
+    let (sql_patch_prefix, sql_patch_suffix) = sql_patch.split_once('\0').unwrap();
+    let sql_patch_prefix = syn::LitStr::new(sql_patch_prefix, input.span());
+    let sql_patch_suffix = syn::LitStr::new(sql_patch_suffix, input.span());
+    let patch_tenant = tenant.map(|_| quote! { q = q.bind(tenant); });
+    let pk_vars = (0..pk_types.len()).map(|i| format_ident!("pk{}", i)).collect::<Vec<_>>();
+    let destructure_id = match pk_vars.as_slice() {
+        [v] => quote! { let #v = id; },
+        _ => quote! { let ( #( #pk_vars ),* ) = id; },
+    };
+    let updatable_trait = match tenant {
+        Some(_) => format_ident!("TenantUpdatable"),
+        None => format_ident!("Updatable"),
     };
+    // bounded like `HasId`, for `InMemoryRepository`
+    let copy_updated = tenant.is_none().then(|| {
+        let upd_types = upd_cols.iter().map(|ci| &ci.ty);
+        quote! {
+            impl shl_sqlx::postgres::CopyUpdated for #ident
+            where #( for<'__u> #upd_types: ::std::clone::Clone ),*
+            {
+                fn copy_updated(&self, row: &mut Self) {
+                    #( row.#patch_fields = ::std::clone::Clone::clone(&self.#patch_fields); )*
+                }
+            }
+        }
+    });
 
     let expanded = quote! {
-        impl shl_sqlx::postgres::Updatable for #ident {
+        /// Partial update of a row: only `Some` fields are written by `update_patch`.
+        #[derive(Default)]
+        #vis struct #patch_ident {
+            #( pub #patch_fields: Option<#patch_types>, )*
+        }
+
+        impl #patch_ident {
+            /// No field is set, so `update_patch` would not touch the database.
+            pub fn is_empty(&self) -> bool {
+                true #( && self.#patch_fields.is_none() )*
+            }
+        }
+
+        impl shl_sqlx::postgres::#updatable_trait for #ident {
+            type Error = #error_ty;
+            type Patch = #patch_ident;
+            type Version = #version_ty;
+
             const SQL_UPDATE: &'static str = #sql_update_lit;
+            const SQL_UPDATE_RETURNING: &'static str = #sql_update_returning_lit;
 
-            async fn update<'e, E>(&self, exec: E) -> Result<u64, sqlx::Error>
+            async fn update<'e, E>(&self, exec: E, #tenant_param) -> Result<u64, Self::Error>
             where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
                 let mut q = sqlx::query(Self::SQL_UPDATE);
                 #( #bind_upd )*
                 #bind_pk
                 let res = q.execute(exec).await?;
+                #check_affected
+                Ok(res.rows_affected())
+            }
+
+            async fn update_returning<'e, E>(&self, exec: E, #tenant_param) -> Result<Self, Self::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                let mut q = sqlx::query_as::<_, Self>(Self::SQL_UPDATE_RETURNING);
+                #( #bind_upd )*
+                #bind_pk
+                let row = #fetch_updated;
+                Ok(row)
+            }
+
+            async fn update_patch<'e, E>(exec: E, #tenant_param id: Self::Id, version: Self::Version, patch: Self::Patch) -> Result<u64, Self::Error>
+            where E: sqlx::Executor<'e, Database = sqlx::Postgres> + Send {
+                let mut sets = Vec::new();
+                #(
+                    if patch.#patch_fields.is_some() {
+                        sets.push(format!("{}{}", #patch_sets, #patch_keys + sets.len() + 1));
+                    }
+                )*
+                if sets.is_empty() {
+                    return Ok(0);
+                }
+                #( sets.push(#patch_touch.to_owned()); )*
+                let sql = format!("{}{}{}", #sql_patch_prefix, sets.join(", "), #sql_patch_suffix);
+                let mut q = sqlx::query(&sql);
+                #destructure_id
+                #( q = q.bind(#pk_vars); )*
+                #patch_version
+                #patch_tenant
+                #(
+                    if let Some(v) = patch.#patch_fields {
+                        q = q.bind(v);
+                    }
+                )*
+                let res = q.execute(exec).await?;
+                #patch_check
                 Ok(res.rows_affected())
             }
         }
+
+        #copy_updated
     };
     expanded.into()
 }
+
+#[proc_macro_error]
+#[proc_macro_derive(PgEnum, attributes(pg_enum))]
+pub fn derive_pg_enum(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    pg_enum::derive(&input).into()
+}
+
+#[proc_macro_error]
+#[proc_macro_derive(PgComposite, attributes(pg_composite))]
+pub fn derive_pg_composite(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    pg_composite::derive(&input).into()
+}
+
+#[proc_macro_error]
+#[proc_macro_derive(PgJsonb, attributes(pg_jsonb))]
+pub fn derive_pg_jsonb(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    pg_jsonb::derive(&input).into()
+}
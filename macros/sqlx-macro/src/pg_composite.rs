@@ -0,0 +1,113 @@
+//! `#[derive(PgComposite)]`: structs mapped to a Postgres composite type.
+use crate::pg_enum::rename;
+use proc_macro_error::abort;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+pub(crate) fn derive(input: &DeriveInput) -> TokenStream2 {
+    let ident = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        abort!(input.span(), "PgComposite can only be derived for structs");
+    };
+    let Fields::Named(fields) = &data.fields else {
+        abort!(input.span(), "PgComposite requires named fields");
+    };
+    if fields.named.is_empty() {
+        abort!(input.span(), "PgComposite requires at least one field");
+    }
+    if !input.generics.params.is_empty() {
+        abort!(input.generics.span(), "PgComposite cannot be derived for generic types");
+    }
+
+    let mut type_name: Option<LitStr> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("pg_composite")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type_name") {
+                type_name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown key; expected type_name=..."))
+            }
+        });
+        if let Err(e) = parsed {
+            abort!(e.span(), e.to_string());
+        }
+    }
+    let type_name = match type_name {
+        Some(t) => t.value(),
+        None => rename(&ident.to_string(), "snake_case").unwrap_or_default(),
+    };
+
+    let mut field_idents = Vec::new();
+    let mut field_types = Vec::new();
+    let mut attributes = Vec::new();
+    for f in &fields.named {
+        let field_ident = f.ident.clone().expect("named field");
+        let mut name = field_ident.to_string();
+        for attr in f.attrs.iter().filter(|a| a.path().is_ident("pg_composite")) {
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown field key; expected rename=..."))
+                }
+            });
+            if let Err(e) = parsed {
+                abort!(e.span(), e.to_string());
+            }
+        }
+        let ty = &f.ty;
+        let quoted = format!("\"{}\"", name);
+        attributes.push(quote! {
+            shl_sqlx::postgres::AttributeDef {
+                name: #quoted,
+                type_info: <#ty as sqlx::Type<sqlx::Postgres>>::type_info,
+            }
+        });
+        field_idents.push(field_ident);
+        field_types.push(ty);
+    }
+
+    quote! {
+        impl shl_sqlx::postgres::PgComposite for #ident {
+            const TYPE_NAME: &'static str = #type_name;
+            const ATTRIBUTES: &'static [shl_sqlx::postgres::AttributeDef] = &[ #( #attributes ),* ];
+        }
+
+        impl sqlx::Type<sqlx::Postgres> for #ident {
+            fn type_info() -> sqlx::postgres::PgTypeInfo {
+                sqlx::postgres::PgTypeInfo::with_name(#type_name)
+            }
+        }
+
+        impl sqlx::postgres::PgHasArrayType for #ident {
+            fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+                sqlx::postgres::PgTypeInfo::array_of(#type_name)
+            }
+        }
+
+        impl<'q> sqlx::Encode<'q, sqlx::Postgres> for #ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut sqlx::postgres::PgArgumentBuffer,
+            ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                let mut encoder = sqlx::postgres::types::PgRecordEncoder::new(buf);
+                #( encoder.encode(&self.#field_idents)?; )*
+                encoder.finish();
+                Ok(sqlx::encode::IsNull::No)
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for #ident {
+            fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+                let mut decoder = sqlx::postgres::types::PgRecordDecoder::new(value)?;
+                Ok(Self {
+                    #( #field_idents: decoder.try_decode::<#field_types>()?, )*
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! `#[derive(PgEnum)]`: unit enums stored as `varchar` or as a native Postgres enum.
+use proc_macro_error::abort;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+const RENAME_RULES: &str = "lowercase, UPPERCASE, PascalCase, camelCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case, SCREAMING-KEBAB-CASE";
+
+/// Splits a `PascalCase` identifier into words, keeping acronyms together (`HTTPServer` -> `HTTP`, `Server`).
+fn words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch == '_' {
+            if !cur.is_empty() {
+                out.push(std::mem::take(&mut cur));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let boundary = ch.is_uppercase()
+            && prev.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit() || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase())));
+        if boundary && !cur.is_empty() {
+            out.push(std::mem::take(&mut cur));
+        }
+        cur.push(ch);
+    }
+    if !cur.is_empty() {
+        out.push(cur);
+    }
+    out
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// Applies a serde-style `rename_all` rule, `None` for an unknown rule.
+pub(crate) fn rename(name: &str, rule: &str) -> Option<String> {
+    let words = words(name);
+    let lower = || words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>();
+    let upper = || words.iter().map(|w| w.to_uppercase()).collect::<Vec<_>>();
+    Some(match rule {
+        "lowercase" => lower().concat(),
+        "UPPERCASE" => upper().concat(),
+        "PascalCase" => words.iter().map(|w| capitalize(w)).collect(),
+        "camelCase" => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.to_lowercase() } else { capitalize(w) })
+            .collect(),
+        "snake_case" => lower().join("_"),
+        "SCREAMING_SNAKE_CASE" => upper().join("_"),
+        "kebab-case" => lower().join("-"),
+        "SCREAMING-KEBAB-CASE" => upper().join("-"),
+        _ => return None,
+    })
+}
+
+/// `"schema"."name"` or `"name"`.
+fn quote_type_name(name: &str) -> String {
+    name.split('.').map(|part| format!("\"{}\"", part)).collect::<Vec<_>>().join(".")
+}
+
+pub(crate) fn derive(input: &DeriveInput) -> TokenStream2 {
+    let ident = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        abort!(input.span(), "PgEnum can only be derived for enums");
+    };
+
+    let mut type_name: Option<LitStr> = None;
+    let mut rename_all: Option<LitStr> = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("pg_enum")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type_name") {
+                type_name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("rename_all") {
+                rename_all = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unknown key; expected type_name=..., rename_all=..."));
+            }
+            Ok(())
+        });
+        if let Err(e) = parsed {
+            abort!(e.span(), e.to_string());
+        }
+    }
+
+    let mut variants = Vec::new();
+    let mut labels = Vec::new();
+    for v in &data.variants {
+        if !matches!(v.fields, Fields::Unit) {
+            abort!(v.span(), "PgEnum variants cannot have fields");
+        }
+        let mut label = None;
+        for attr in v.attrs.iter().filter(|a| a.path().is_ident("pg_enum")) {
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    label = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown variant key; expected rename=..."))
+                }
+            });
+            if let Err(e) = parsed {
+                abort!(e.span(), e.to_string());
+            }
+        }
+        let label = match (label, &rename_all) {
+            (Some(l), _) => l,
+            (None, Some(rule)) => match rename(&v.ident.to_string(), &rule.value()) {
+                Some(l) => l,
+                None => abort!(rule.span(), format!("unknown rename_all rule; expected one of {}", RENAME_RULES)),
+            },
+            (None, None) => v.ident.to_string(),
+        };
+        if labels.contains(&label) {
+            abort!(v.span(), format!("duplicate label '{}'", label));
+        }
+        variants.push(&v.ident);
+        labels.push(label);
+    }
+    if variants.is_empty() {
+        abort!(input.span(), "PgEnum requires at least one variant");
+    }
+
+    let name = ident.to_string();
+    let (type_name, array_type, create_type) = match &type_name {
+        Some(t) => {
+            let t = t.value();
+            let values = labels
+                .iter()
+                .map(|l| format!("'{}'", l.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let ddl = format!("CREATE TYPE {} AS ENUM ({})", quote_type_name(&t), values);
+            (t.clone(), quote! { sqlx::postgres::PgTypeInfo::array_of(#t) }, quote! { Some(#ddl) })
+        }
+        None => (
+            "varchar".to_owned(),
+            quote! { sqlx::postgres::PgTypeInfo::with_name("_varchar") },
+            quote! { None },
+        ),
+    };
+    // varchar enums also decode from `text` columns and expressions
+    let compatible = (type_name == "varchar").then(|| {
+        quote! {
+            fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+                <&str as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+            }
+        }
+    });
+
+    quote! {
+        impl shl_sqlx::postgres::PgEnum for #ident {
+            const TYPE_NAME: &'static str = #type_name;
+            const ALL_VARIANTS: &'static [Self] = &[ #( Self::#variants ),* ];
+            const LABELS: &'static [&'static str] = &[ #( #labels ),* ];
+            const SQL_CREATE_TYPE: Option<&'static str> = #create_type;
+
+            fn as_str(&self) -> &'static str {
+                match self {
+                    #( Self::#variants => #labels, )*
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(shl_sqlx::postgres::PgEnum::as_str(self))
+            }
+        }
+
+        impl ::std::str::FromStr for #ident {
+            type Err = shl_sqlx::postgres::ParseEnumError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    #( #labels => Ok(Self::#variants), )*
+                    _ => Err(shl_sqlx::postgres::ParseEnumError {
+                        type_name: #name,
+                        value: s.to_owned(),
+                    }),
+                }
+            }
+        }
+
+        impl sqlx::Type<sqlx::Postgres> for #ident {
+            fn type_info() -> sqlx::postgres::PgTypeInfo {
+                sqlx::postgres::PgTypeInfo::with_name(#type_name)
+            }
+
+            #compatible
+        }
+
+        impl sqlx::postgres::PgHasArrayType for #ident {
+            fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+                #array_type
+            }
+        }
+
+        impl<'q> sqlx::Encode<'q, sqlx::Postgres> for #ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut sqlx::postgres::PgArgumentBuffer,
+            ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                <&str as sqlx::Encode<sqlx::Postgres>>::encode(shl_sqlx::postgres::PgEnum::as_str(self), buf)
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for #ident {
+            fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+                let s = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+                Ok(s.parse()?)
+            }
+        }
+    }
+}
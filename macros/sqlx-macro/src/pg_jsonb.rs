@@ -0,0 +1,87 @@
+//! `#[derive(PgJsonb)]`: serde types stored as `jsonb`, expanding to `impl_serde_jsonb!`.
+use proc_macro_error::abort;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{DeriveInput, LitInt, LitStr};
+
+pub(crate) fn derive(input: &DeriveInput) -> TokenStream2 {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        abort!(input.generics.span(), "PgJsonb cannot be derived for generic types");
+    }
+
+    let mut type_name: Option<LitStr> = None;
+    let mut version: Option<LitInt> = None;
+    let mut upgrades: Vec<(LitInt, syn::Path)> = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("pg_jsonb")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type_name") {
+                let lit = meta.value()?.parse::<LitStr>()?;
+                if !matches!(lit.value().as_str(), "json" | "jsonb") {
+                    return Err(syn::Error::new(lit.span(), "type_name must be \"json\" or \"jsonb\""));
+                }
+                type_name = Some(lit);
+            } else if meta.path.is_ident("version") {
+                let lit = meta.value()?.parse::<LitInt>()?;
+                lit.base10_parse::<u32>()?;
+                version = Some(lit);
+            } else if meta.path.is_ident("upgrade") {
+                let mut from: Option<LitInt> = None;
+                let mut with: Option<syn::Path> = None;
+                meta.parse_nested_meta(|inner| {
+                    if inner.path.is_ident("from") {
+                        let lit = inner.value()?.parse::<LitInt>()?;
+                        lit.base10_parse::<u32>()?;
+                        from = Some(lit);
+                    } else if inner.path.is_ident("with") {
+                        with = Some(inner.value()?.parse::<LitStr>()?.parse()?);
+                    } else {
+                        return Err(inner.error("unknown upgrade key; expected from=..., with=\"...\""));
+                    }
+                    Ok(())
+                })?;
+                match (from, with) {
+                    (Some(from), Some(with)) => upgrades.push((from, with)),
+                    _ => return Err(meta.error("upgrade requires from=... and with=\"...\"")),
+                }
+            } else {
+                return Err(meta.error("unknown key; expected type_name=..., version=..., upgrade(...)"));
+            }
+            Ok(())
+        });
+        if let Err(e) = parsed {
+            abort!(e.span(), e.to_string());
+        }
+    }
+
+    if let Some((from, _)) = upgrades.first()
+        && version.is_none()
+    {
+        abort!(from.span(), "upgrade(...) requires version = ...");
+    }
+    if let Some(current) = &version {
+        let current_v = current.base10_parse::<u32>().unwrap_or_default();
+        let mut seen = Vec::new();
+        for (from, _) in &upgrades {
+            let v = from.base10_parse::<u32>().unwrap_or_default();
+            if v >= current_v {
+                abort!(from.span(), format!("upgrade from version {} must be below version {}", v, current_v));
+            }
+            if seen.contains(&v) {
+                abort!(from.span(), format!("duplicate upgrade from version {}", v));
+            }
+            seen.push(v);
+        }
+    }
+
+    let type_arg = type_name.map(|t| quote! { , #t });
+    let version_arg = version.map(|v| {
+        let froms = upgrades.iter().map(|(from, _)| from);
+        let withs = upgrades.iter().map(|(_, with)| with);
+        quote! { , version = #v, upgrades = { #( #froms => #withs ),* } }
+    });
+    quote! {
+        shl_sqlx::impl_serde_jsonb!(#ident #type_arg #version_arg);
+    }
+}
@@ -0,0 +1,132 @@
+//! `#[table(verify)]`: checks a `Table` derive against the offline schema snapshot written by
+//! `shl_sqlx::postgres::schema::Snapshot`.
+use crate::{ColInfo, unquote};
+use proc_macro_error::{abort, emit_error};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use serde_json::Value;
+use std::path::PathBuf;
+use syn::spanned::Spanned;
+use syn::{GenericArgument, PathArguments, Type};
+
+/// Overrides the snapshot location; relative paths are resolved against `CARGO_MANIFEST_DIR`.
+const SNAPSHOT_ENV: &str = "SHL_SQLX_SCHEMA";
+const SNAPSHOT_FILE: &str = "shl-sqlx-schema.json";
+
+fn snapshot_path() -> PathBuf {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    match std::env::var(SNAPSHOT_ENV) {
+        Ok(p) => manifest_dir.join(p),
+        Err(_) => manifest_dir.join(SNAPSHOT_FILE),
+    }
+}
+
+/// Emits an error for every column that is missing or whose Rust type doesn't fit, and returns
+/// an `include_bytes!` of the snapshot so the derive is re-checked when the file changes.
+pub(crate) fn verify(span: Span, schema: &str, table: &str, cols: &[ColInfo]) -> TokenStream2 {
+    let path = snapshot_path();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(e) => abort!(span, "cannot read schema snapshot {}: {}", path.display(), e),
+    };
+    let snapshot: Value = match serde_json::from_str(&text) {
+        Ok(v) => v,
+        Err(e) => abort!(span, "invalid schema snapshot {}: {}", path.display(), e),
+    };
+
+    let key = format!("{}.{}", schema, table);
+    let Some(columns) = snapshot["tables"][&key]["columns"].as_object() else {
+        abort!(span, "table {} not found in schema snapshot {}", key, path.display());
+    };
+
+    for ci in cols {
+        let name = unquote(&ci.sql_quoted);
+        let Some(col) = columns.get(&name) else {
+            emit_error!(ci.rs_ident.span(), "column \"{}\" not found in {}", name, key);
+            continue;
+        };
+        let pg_type = col["type"].as_str().unwrap_or_default();
+        let (ty, optional) = strip_option(&ci.ty);
+        if col["nullable"].as_bool().unwrap_or(true) && !optional {
+            emit_error!(ci.ty.span(), "column \"{}\" is nullable, use Option<_>", name);
+        }
+        if let Some(expected) = ci.pg_type.clone().map(|t| vec![t]).or_else(|| pg_types(ty))
+            && !expected.iter().any(|t| t == pg_type)
+        {
+            emit_error!(
+                ci.ty.span(),
+                "column \"{}\" is `{}`, this type maps to {}",
+                name,
+                pg_type,
+                expected.join(" | ")
+            );
+        }
+    }
+
+    let path = path.to_string_lossy();
+    quote! { const _: &[u8] = include_bytes!(#path); }
+}
+
+/// Last path segment of `ty` with its first generic type argument.
+fn last_segment(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let Type::Path(tp) = ty else { return None };
+    let seg = tp.path.segments.last()?;
+    let arg = match &seg.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|a| match a {
+            GenericArgument::Type(t) => Some(t),
+            _ => None,
+        }),
+        _ => None,
+    };
+    Some((seg.ident.to_string(), arg))
+}
+
+pub(crate) fn strip_option(ty: &Type) -> (&Type, bool) {
+    match last_segment(ty) {
+        Some((name, Some(inner))) if name == "Option" => (inner, true),
+        _ => (ty, false),
+    }
+}
+
+/// `Encrypted<T>` or `Option<Encrypted<T>>`.
+pub(crate) fn is_encrypted(ty: &Type) -> bool {
+    let (ty, _) = strip_option(ty);
+    matches!(last_segment(ty), Some((name, Some(_))) if name == "Encrypted")
+}
+
+/// Postgres `udt_name`s accepted for a Rust type, `None` when the type is unknown (custom enums,
+/// domain wrappers, ...) and only the column's presence is checked.
+fn pg_types(ty: &Type) -> Option<Vec<String>> {
+    if let Type::Reference(r) = ty {
+        return pg_types(&r.elem);
+    }
+    let (name, arg) = last_segment(ty)?;
+    let names: &[&str] = match name.as_str() {
+        "bool" => &["bool"],
+        "i8" => &["char"],
+        "i16" => &["int2"],
+        "i32" => &["int4"],
+        "i64" => &["int8"],
+        "f32" => &["float4"],
+        "f64" => &["float8"],
+        "String" | "str" => &["text", "varchar", "bpchar", "name", "citext"],
+        "Encrypted" => &["text", "varchar"],
+        "Uuid" => &["uuid"],
+        "DateTime" | "OffsetDateTime" => &["timestamptz"],
+        "NaiveDateTime" | "PrimitiveDateTime" => &["timestamp"],
+        "NaiveDate" | "Date" => &["date"],
+        "NaiveTime" | "Time" => &["time"],
+        "Decimal" | "BigDecimal" => &["numeric"],
+        "Value" | "JsonValue" | "Json" => &["json", "jsonb"],
+        "IpAddr" | "IpNetwork" => &["inet", "cidr"],
+        "Vec" => {
+            let inner = arg?;
+            if last_segment(inner).is_some_and(|(n, _)| n == "u8") {
+                return Some(vec!["bytea".into()]);
+            }
+            return Some(pg_types(inner)?.into_iter().map(|t| format!("_{}", t)).collect());
+        }
+        _ => return None,
+    };
+    Some(names.iter().map(|s| s.to_string()).collect())
+}
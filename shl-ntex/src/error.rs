@@ -1,6 +1,7 @@
 pub use ntex_error_macro::*;
 use serde::Serialize;
 use std::collections::HashMap;
+#[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
 
 #[derive(Debug, Serialize)]
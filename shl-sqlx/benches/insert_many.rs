@@ -0,0 +1,75 @@
+//! Compares `BulkInsertable::insert_many` with one `insert` per row.
+//!
+//! ```sh
+//! DATABASE_URL=postgres://... cargo bench -p shl-sqlx --features postgres --bench insert_many
+//! ```
+use shl_sqlx::postgres::{BulkInsertable, Insertable};
+use shl_sqlx::{Insertable, Table};
+use sqlx::{Connection, FromRow, PgConnection};
+use std::time::{Duration, Instant};
+
+#[derive(FromRow, Table, Insertable)]
+#[table(schema = "pg_temp", table = "bench_events", bulk)]
+pub struct Event {
+    pub id: i64,
+    pub kind: String,
+    pub payload: Option<String>,
+    pub score: i32,
+}
+
+fn rows(n: i64) -> Vec<Event> {
+    (0..n)
+        .map(|id| Event {
+            id,
+            kind: format!("kind-{}", id % 7),
+            payload: (id % 3 == 0).then(|| "x".repeat(64)),
+            score: id as i32,
+        })
+        .collect()
+}
+
+async fn reset(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query("DROP TABLE IF EXISTS pg_temp.bench_events").execute(&mut *conn).await?;
+    sqlx::query("CREATE TEMP TABLE bench_events (id BIGINT PRIMARY KEY, kind TEXT NOT NULL, payload TEXT, score INT NOT NULL)")
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+async fn per_row(conn: &mut PgConnection, rows: &[Event]) -> Result<Duration, sqlx::Error> {
+    reset(conn).await?;
+    let started = Instant::now();
+    let mut tx = conn.begin().await?;
+    for row in rows {
+        row.insert(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(started.elapsed())
+}
+
+async fn batched(conn: &mut PgConnection, rows: &[Event]) -> Result<Duration, sqlx::Error> {
+    reset(conn).await?;
+    let started = Instant::now();
+    Event::insert_many(&mut *conn, rows).await?;
+    Ok(started.elapsed())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping insert_many benchmark");
+        return Ok(());
+    };
+    let mut conn = PgConnection::connect(&url).await?;
+
+    for n in [100, 1_000, 10_000] {
+        let rows = rows(n);
+        let per_row = per_row(&mut conn, &rows).await?;
+        let batched = batched(&mut conn, &rows).await?;
+        println!(
+            "{n:>6} rows: insert {per_row:>10.2?}  insert_many {batched:>10.2?}  ({:.1}x)",
+            per_row.as_secs_f64() / batched.as_secs_f64()
+        );
+    }
+    Ok(())
+}
@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
-use shl_sqlx::postgres::{Insertable, Readable, Updatable};
+use shl_sqlx::postgres::{Insertable, Queryable, Readable, Updatable, Upsertable};
 use shl_sqlx::uuid::uuidv7_and_created_at;
-use shl_sqlx::{Insertable, Table, Updatable};
+use shl_sqlx::{Insertable, PgEnum, Table, Updatable};
 use sqlx::FromRow;
 use uuid::Uuid;
 
 #[derive(Debug, FromRow, Table, Insertable, Updatable)]
+#[table(has_many(Integration, fk = "user_id"))]
 pub struct User {
     pub id: Uuid,
     pub name: String,
@@ -20,14 +21,14 @@ pub struct User {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, PartialEq, PartialOrd, sqlx::Type)]
-#[sqlx(type_name = "integration_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, PartialEq, PartialOrd, PgEnum)]
+#[pg_enum(type_name = "integration_kind", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum IntegrationKind {
     Google,
 }
 
 #[derive(Debug, FromRow, Table, Insertable)]
-#[table(pk("kind", "external_identifier"))]
+#[table(pk("kind", "external_identifier"), belongs_to(User, fk = "user_id"))]
 pub struct Integration {
     pub kind: IntegrationKind,
     pub external_identifier: String,
@@ -60,15 +61,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     user.avatar_s3_key = Some("file.png".to_owned());
     user.update(&pool).await?;
 
+    user.name = "alice2".into();
+    let user = user.update_returning(&pool).await?;
+
     let integration = Integration {
         kind: IntegrationKind::Google,
         external_identifier: "123456789".to_owned(),
-        user_id: id,
+        user_id: user.id,
         created_at,
     };
-    integration.insert(&pool).await?;
+    integration.upsert(&pool).await?;
+
+    let integration = Integration::find_by_id(&pool, (IntegrationKind::Google, "123456789".to_owned())).await?;
+    let owner = integration.user(&pool).await?;
+    let _ = User::load_integrations(&pool, &[owner]).await?;
 
-    let _ = Integration::find_by_id(&pool, (IntegrationKind::Google, "123456789".to_owned())).await?;
+    let cols = User::cols();
+    let _ = User::select()
+        .filter(cols.name.eq("alice2".to_owned()).and(cols.banned.eq(false)))
+        .order_by(cols.created_at.desc())
+        .limit(10)
+        .fetch_all(&pool)
+        .await?;
 
     Ok(())
 }
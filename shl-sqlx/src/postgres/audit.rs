@@ -0,0 +1,129 @@
+//! Change history written by `#[table(audit)]` types.
+//!
+//! Every write of such types (`insert`, `upsert`, `update`, `update_patch`, `delete_by_id`,
+//! `restore_by_id`, `purge_by_id`, ...) runs as a single statement that also inserts one row per
+//! affected row into the audit table, so the history is committed or rolled back together with the
+//! change. The bulk paths (`insert_many`, `copy_in`) can't write audit rows and are not available
+//! for them: `#[table(bulk)]`, which implements [`BulkInsertable`](super::BulkInsertable), is
+//! rejected on audited types.
+//!
+//! Values are stored as Postgres' `to_jsonb` of the row, keyed by column name, not as the
+//! `serde_json` form of the Rust type: the old row only exists in the database when the statement
+//! runs. They follow Postgres' JSON rendering instead of the type's `Serialize` impl, so
+//! `timestamptz` is an ISO string with the session offset, `bytea` a `\x...` hex string, enums their
+//! label, `jsonb` columns nested as is and `#[table(encrypted)]` columns the stored ciphertext.
+//!
+//! The actor is read from the transaction-local [`ACTOR_SETTING`], so [`set_actor`] takes the
+//! transaction the audited writes run in:
+//!
+//! ```ignore
+//! let mut tx = pool.begin().await?;
+//! audit::set_actor(&mut tx, &user_id.to_string()).await?;
+//! post.update(&mut *tx).await?;
+//! tx.commit().await?;
+//! ```
+use super::TableMeta;
+use serde_json::Value;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Error, Executor, FromRow, Postgres, Transaction, Type};
+use std::fmt;
+
+/// Setting holding the actor id recorded in audit rows, `NULL` when unset.
+pub const ACTOR_SETTING: &str = "shl_sqlx.actor_id";
+
+/// Audit table used by a bare `#[table(audit)]`.
+pub const DEFAULT_TABLE: &str = "\"public\".\"audit_log\"";
+
+/// Sets the actor recorded by audited statements until the end of `tx`. Outside a transaction
+/// the setting would be gone before the next statement, so there is no variant taking a pool.
+pub async fn set_actor(tx: &mut Transaction<'_, Postgres>, actor_id: &str) -> Result<(), Error> {
+    sqlx::query("SELECT set_config($1, $2, true)")
+        .bind(ACTOR_SETTING)
+        .bind(actor_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// `CREATE TABLE` for an audit table with the columns written by the derives.
+pub fn create_table_sql(qual_table: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n    \"id\" bigserial PRIMARY KEY,\n    \"table_name\" text NOT NULL,\n    \"operation\" text NOT NULL,\n    \"pk\" jsonb NOT NULL,\n    \"old_values\" jsonb,\n    \"new_values\" jsonb,\n    \"actor_id\" text,\n    \"changed_at\" timestamptz NOT NULL DEFAULT now()\n)",
+        qual_table
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Operation::Insert => "INSERT",
+            Operation::Update => "UPDATE",
+            Operation::Delete => "DELETE",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Type<Postgres> for Operation {
+    fn type_info() -> PgTypeInfo {
+        <&str as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <&str as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Operation {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        match <&str as Decode<Postgres>>::decode(value)? {
+            "INSERT" => Ok(Operation::Insert),
+            "UPDATE" => Ok(Operation::Update),
+            "DELETE" => Ok(Operation::Delete),
+            other => Err(format!("unknown audit operation '{}'", other).into()),
+        }
+    }
+}
+
+/// A row of the audit table.
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// `schema.table` of the changed row.
+    pub table_name: String,
+    pub operation: Operation,
+    /// Primary key columns of the changed row.
+    pub pk: Value,
+    /// `NULL` for inserts.
+    pub old_values: Option<Value>,
+    /// `NULL` for deletes.
+    pub new_values: Option<Value>,
+    pub actor_id: Option<String>,
+}
+
+/// Audit rows of `T`'s table, oldest first. `pk` is matched by containment, so
+/// `json!({"id": 1})` finds the history of one row.
+pub async fn history<'e, T, E>(exec: E, pk: Value) -> Result<Vec<AuditEntry>, Error>
+where
+    T: TableMeta,
+    E: Executor<'e, Database = Postgres>,
+{
+    let sql = format!(
+        "SELECT \"id\", \"table_name\", \"operation\", \"pk\", \"old_values\", \"new_values\", \"actor_id\" FROM {} WHERE \"table_name\" = $1 AND \"pk\" @> $2 ORDER BY \"id\"",
+        T::AUDIT_TABLE.unwrap_or(DEFAULT_TABLE)
+    );
+    sqlx::query_as(&sql).bind(T::QUAL_TABLE.replace('"', "")).bind(pk).fetch_all(exec).await
+}
@@ -0,0 +1,354 @@
+//! Read-through caching of [`Readable::find_by_id`] in a [`CacheService`].
+//!
+//! Rows are cached as JSON under `shl_sqlx:<schema>.<table>:<id>` (see [`CachedReadable::cache_key`])
+//! and invalidated by the `_cached` variants of the write methods. [`CachedRepository`] runs every
+//! update and delete through them, so services holding it can't skip the invalidation:
+//!
+//! ```ignore
+//! let users = CachedRepository::<User, _>::new(pool, cache);
+//! let mut user = users.find_by_id(id).await?;
+//! user.name = name;
+//! users.update(&user).await?;
+//!
+//! // or per call
+//! let user = User::find_by_id_cached(&pool, &cache, id).await?;
+//! user.update_cached(&pool, &cache).await?;
+//! User::delete_by_id_cached(&pool, &cache, id).await?;
+//! ```
+//!
+//! Invalidation runs right after the statement. Inside a transaction a concurrent reader can cache
+//! the old row again before the commit, so call [`CachedReadable::invalidate`] once more after
+//! committing. Writes that bypass both (the plain `update`, `upsert`, raw SQL, ...) are not seen
+//! until the entry expires. Misses and soft-deleted rows are not cached, so inserts,
+//! `insert_or_ignore` and restores need no invalidation.
+//!
+//! The cache only speeds up reads: when it is unreachable or holds an entry that no longer
+//! deserializes, [`CachedReadable::find_by_id_cached`] logs a warning and reads the database.
+//! Failing to drop an entry is still an error, since the stale row would be served until it expires.
+use super::{BulkInsertable, DbError, HasId, Insertable, Readable, Repository, SoftDeletable, Updatable, Upsertable};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use shl_redis_cache_service::error::Error;
+use shl_redis_cache_service::{CacheClient, CacheService};
+use sqlx::{Executor, PgPool, Postgres};
+use std::marker::PhantomData;
+
+/// Prefix of every key written by [`CachedReadable`].
+pub const KEY_PREFIX: &str = "shl_sqlx";
+
+#[derive(thiserror::Error, Debug)]
+pub enum CacheError<E = sqlx::Error> {
+    #[error(transparent)]
+    Db(E),
+
+    #[error(transparent)]
+    Cache(#[from] Error),
+}
+
+/// Implemented for every [`Readable`] row that is `Serialize + DeserializeOwned` with a
+/// `Serialize` id.
+pub trait CachedReadable: Readable + Serialize + DeserializeOwned + Send + Sync
+where
+    Self::Id: Serialize + Send,
+{
+    /// `shl_sqlx:public.users:42`; string ids are used as is, other ids as JSON
+    /// (`[1,"a"]` for a composite key). Fails when the id does not serialize; such rows are never
+    /// cached.
+    fn cache_key(id: &Self::Id) -> Result<String, Error> {
+        let id = match serde_json::to_value(id)? {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        Ok(format!("{}:{}:{}", KEY_PREFIX, Self::QUAL_TABLE.replace('"', ""), id))
+    }
+
+    /// Pattern matching every cached row of the table, for [`CacheService::delete_pattern`].
+    fn cache_pattern() -> String {
+        format!("{}:{}:*", KEY_PREFIX, Self::QUAL_TABLE.replace('"', ""))
+    }
+
+    /// Returns the cached row, or reads it with [`Readable::find_by_id`] and caches it. Cache errors
+    /// are logged and only the database can fail.
+    fn find_by_id_cached<'e, E, C>(exec: E, cache: &'e CacheService<C>, id: Self::Id) -> impl Future<Output = Result<Self, CacheError>> + Send + 'e
+    where
+        Self: 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        // futures are lazy, so the query only runs on a miss
+        let key = Self::cache_key(&id);
+        let find = Self::find_by_id(exec, id);
+        async move {
+            let key = match key {
+                Ok(key) => key,
+                Err(e) => {
+                    tracing::warn!(table = Self::QUAL_TABLE, error = %e, "uncacheable id");
+                    return find.await.map_err(CacheError::Db);
+                }
+            };
+            match cache.get(&key).await {
+                Ok(Some(row)) => return Ok(row),
+                Ok(None) => {}
+                Err(e) => tracing::warn!(key, error = %e, "cache read failed"),
+            }
+            let row = find.await.map_err(CacheError::Db)?;
+            if let Err(e) = cache.set(&key, &row).await {
+                tracing::warn!(key, error = %e, "cache write failed");
+            }
+            Ok(row)
+        }
+    }
+
+    /// Drops the cached row of `id`.
+    fn invalidate<'e, C>(cache: &'e CacheService<C>, id: &Self::Id) -> impl Future<Output = Result<(), CacheError>> + Send + 'e
+    where
+        C: CacheClient,
+    {
+        let key = Self::cache_key(id);
+        drop_key(cache, key)
+    }
+
+    /// [`Updatable::update`], then drops the cached row.
+    fn update_cached<'e, E, C>(
+        &'e self,
+        exec: E,
+        cache: &'e CacheService<C>,
+    ) -> impl Future<Output = Result<u64, CacheError<Self::Error>>> + Send + 'e
+    where
+        Self: Updatable + HasId,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        async move {
+            let affected = self.update(exec).await.map_err(CacheError::Db)?;
+            drop_key(cache, Self::cache_key(&self.id())).await?;
+            Ok(affected)
+        }
+    }
+
+    /// [`Updatable::update_returning`], then drops the cached row.
+    fn update_returning_cached<'e, E, C>(
+        &'e self,
+        exec: E,
+        cache: &'e CacheService<C>,
+    ) -> impl Future<Output = Result<Self, CacheError<Self::Error>>> + Send + 'e
+    where
+        Self: Updatable + HasId,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        async move {
+            let row = self.update_returning(exec).await.map_err(CacheError::Db)?;
+            drop_key(cache, Self::cache_key(&self.id())).await?;
+            Ok(row)
+        }
+    }
+
+    /// [`Updatable::update_patch`], then drops the cached row.
+    fn update_patch_cached<'e, E, C>(
+        exec: E,
+        cache: &'e CacheService<C>,
+        id: Self::Id,
+        version: Self::Version,
+        patch: Self::Patch,
+    ) -> impl Future<Output = Result<u64, CacheError<Self::Error>>> + Send + 'e
+    where
+        Self: Updatable,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        let key = Self::cache_key(&id);
+        let update = Self::update_patch(exec, id, version, patch);
+        async move {
+            let affected = update.await.map_err(CacheError::Db)?;
+            drop_key(cache, key).await?;
+            Ok(affected)
+        }
+    }
+
+    /// [`Upsertable::upsert`], then drops the cached row it may have updated.
+    fn upsert_cached<'e, E, C>(&'e self, exec: E, cache: &'e CacheService<C>) -> impl Future<Output = Result<u64, CacheError>> + Send + 'e
+    where
+        Self: Upsertable + HasId,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        async move {
+            let affected = self.upsert(exec).await.map_err(CacheError::Db)?;
+            drop_key(cache, Self::cache_key(&self.id())).await?;
+            Ok(affected)
+        }
+    }
+
+    /// [`Readable::delete_by_id`], then drops the cached row.
+    fn delete_by_id_cached<'e, E, C>(exec: E, cache: &'e CacheService<C>, id: Self::Id) -> impl Future<Output = Result<u64, CacheError>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        let key = Self::cache_key(&id);
+        let delete = Self::delete_by_id(exec, id);
+        async move {
+            let affected = delete.await.map_err(CacheError::Db)?;
+            drop_key(cache, key).await?;
+            Ok(affected)
+        }
+    }
+
+    /// [`Readable::delete_returning`], then drops the cached row.
+    fn delete_returning_cached<'e, E, C>(
+        exec: E,
+        cache: &'e CacheService<C>,
+        id: Self::Id,
+    ) -> impl Future<Output = Result<Self, CacheError>> + Send + 'e
+    where
+        Self: 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        let key = Self::cache_key(&id);
+        let delete = Self::delete_returning(exec, id);
+        async move {
+            let row = delete.await.map_err(CacheError::Db)?;
+            drop_key(cache, key).await?;
+            Ok(row)
+        }
+    }
+
+    /// [`SoftDeletable::purge_by_id`], then drops the cached row.
+    fn purge_by_id_cached<'e, E, C>(exec: E, cache: &'e CacheService<C>, id: Self::Id) -> impl Future<Output = Result<u64, CacheError>> + Send + 'e
+    where
+        Self: SoftDeletable,
+        E: Executor<'e, Database = Postgres> + Send + 'e,
+        C: CacheClient,
+    {
+        let key = Self::cache_key(&id);
+        let purge = Self::purge_by_id(exec, id);
+        async move {
+            let affected = purge.await.map_err(CacheError::Db)?;
+            drop_key(cache, key).await?;
+            Ok(affected)
+        }
+    }
+}
+
+impl<T> CachedReadable for T
+where
+    T: Readable + Serialize + DeserializeOwned + Send + Sync,
+    T::Id: Serialize + Send,
+{
+}
+
+/// Deletes `key`; nothing is cached under an id without a key.
+async fn drop_key<C: CacheClient, E>(cache: &CacheService<C>, key: Result<String, Error>) -> Result<(), CacheError<E>> {
+    if let Ok(key) = key {
+        cache.delete(&key).await?;
+    }
+    Ok(())
+}
+
+/// [`Repository`] that reads by id through the cache and drops the cached row on every update and
+/// delete, so services written against [`Repository`] can't leave stale entries behind.
+///
+/// A failed invalidation is returned as a `sqlx::Error::Io` wrapping the cache error (a
+/// [`DbError::Other`]), as the write already went through and the row may now be stale.
+pub struct CachedRepository<T, C: CacheClient> {
+    pool: PgPool,
+    cache: CacheService<C>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, C: CacheClient> CachedRepository<T, C> {
+    pub fn new(pool: PgPool, cache: CacheService<C>) -> Self {
+        Self {
+            pool,
+            cache,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    pub fn cache(&self) -> &CacheService<C> {
+        &self.cache
+    }
+}
+
+impl<T, C: CacheClient + Clone> Clone for CachedRepository<T, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.pool.clone(), self.cache.clone())
+    }
+}
+
+impl<T, C> Repository<T> for CachedRepository<T, C>
+where
+    T: CachedReadable + Insertable + Updatable + HasId,
+    T::Id: Serialize + Send + Sync,
+    C: CacheClient,
+{
+    async fn find_by_id(&self, id: T::Id) -> Result<T, DbError> {
+        T::find_by_id_cached(&self.pool, &self.cache, id).await.map_err(db_error)
+    }
+
+    async fn find_optional_by_id(&self, id: T::Id) -> Result<Option<T>, DbError> {
+        match self.find_by_id(id).await {
+            Ok(row) => Ok(Some(row)),
+            Err(DbError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn find_by_ids(&self, ids: &[T::Id]) -> Result<Vec<T>, DbError> {
+        Ok(T::find_by_ids(&self.pool, ids).await?)
+    }
+
+    async fn exists_by_id(&self, id: T::Id) -> Result<bool, DbError> {
+        Ok(T::exists_by_id(&self.pool, id).await?)
+    }
+
+    async fn count(&self) -> Result<i64, DbError> {
+        Ok(T::count(&self.pool).await?)
+    }
+
+    async fn insert(&self, row: &T) -> Result<u64, DbError> {
+        Ok(row.insert(&self.pool).await?)
+    }
+
+    async fn insert_returning(&self, row: &T) -> Result<T, DbError> {
+        Ok(row.insert_returning(&self.pool).await?)
+    }
+
+    async fn insert_many(&self, rows: &[T]) -> Result<u64, DbError>
+    where
+        T: BulkInsertable,
+    {
+        Ok(T::insert_many(&self.pool, rows).await?)
+    }
+
+    async fn update(&self, row: &T) -> Result<u64, T::Error> {
+        row.update_cached(&self.pool, &self.cache).await.map_err(write_error)
+    }
+
+    async fn update_returning(&self, row: &T) -> Result<T, T::Error> {
+        row.update_returning_cached(&self.pool, &self.cache).await.map_err(write_error)
+    }
+
+    async fn delete_by_id(&self, id: T::Id) -> Result<u64, DbError> {
+        T::delete_by_id_cached(&self.pool, &self.cache, id).await.map_err(db_error)
+    }
+}
+
+fn db_error(e: CacheError) -> DbError {
+    write_error(e).into()
+}
+
+/// The error of a write through [`CachedRepository`], see its docs for cache errors.
+fn write_error<E: From<sqlx::Error>>(e: CacheError<E>) -> E {
+    match e {
+        CacheError::Db(e) => e,
+        CacheError::Cache(e) => sqlx::Error::Io(std::io::Error::other(e)).into(),
+    }
+}
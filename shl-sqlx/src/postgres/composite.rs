@@ -0,0 +1,57 @@
+use sqlx::TypeInfo;
+use sqlx::postgres::PgTypeInfo;
+
+/// An attribute of a composite type as declared by `#[derive(PgComposite)]`.
+#[derive(Debug, Clone, Copy)]
+pub struct AttributeDef {
+    /// Quoted attribute name.
+    pub name: &'static str,
+    /// The field's `sqlx::Type`.
+    pub type_info: fn() -> PgTypeInfo,
+}
+
+/// Implemented by `#[derive(PgComposite)]` for structs mapped to a Postgres composite type,
+/// together with the sqlx `Type`/`Encode`/`Decode`/`PgHasArrayType` traits.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, PgComposite)]
+/// #[pg_composite(type_name = "address")]
+/// pub struct Address {
+///     pub street: String,
+///     #[pg_composite(rename = "zip_code")]
+///     pub zip: Option<String>,
+/// }
+/// ```
+///
+/// Fields are encoded by position, so their order must match the attributes of the type, which
+/// [`create_type_sql`](Self::create_type_sql) can create. Postgres checks the type of every
+/// attribute of a bound record exactly, so the attribute types are the field types (`text` for
+/// `String`; an `impl_to_string_varchar!` type for `varchar`). Without `type_name` the struct name
+/// in `snake_case` is used.
+pub trait PgComposite {
+    const TYPE_NAME: &'static str;
+    /// Attributes in field order.
+    const ATTRIBUTES: &'static [AttributeDef];
+
+    /// `CREATE TYPE ... AS (...)` from `ATTRIBUTES`.
+    fn create_type_sql() -> String {
+        let attributes = Self::ATTRIBUTES
+            .iter()
+            .map(|attr| format!("{} {}", attr.name, ddl_type(&(attr.type_info)())))
+            .collect::<Vec<_>>();
+        format!("CREATE TYPE {} AS ({})", quote_type_name(Self::TYPE_NAME), attributes.join(", "))
+    }
+}
+
+/// Type name for DDL, `TEXT[]` rather than sqlx' `_text` for arrays of declared types.
+pub(crate) fn ddl_type(type_info: &PgTypeInfo) -> String {
+    let name = type_info.name().to_owned();
+    match name.strip_prefix('_') {
+        Some(elem) => format!("{}[]", elem),
+        None => name,
+    }
+}
+
+fn quote_type_name(name: &str) -> String {
+    name.split('.').map(|part| format!("\"{}\"", part)).collect::<Vec<_>>().join(".")
+}
@@ -0,0 +1,121 @@
+//! Bulk loading through `COPY ... FROM STDIN (FORMAT binary)`, for `#[table(bulk)]` types.
+//!
+//! ```ignore
+//! let rows = futures::stream::iter(events);
+//! let written = shl_sqlx::postgres::copy::copy_in::<Event, _, _>(&pool, rows).await?;
+//! ```
+//!
+//! Every field is sent in Postgres' binary representation, so the Rust types must match the column
+//! types exactly (`i32` for `INT`, `i64` for `BIGINT`, ...). `auto_now` columns are written from the
+//! struct values, which is usually what an import wants.
+use super::{BulkInsertable, Insertable};
+use futures_core::Stream;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::PgArgumentBuffer;
+use sqlx::{Acquire, Encode, Error, Postgres};
+use std::pin::pin;
+
+const SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+/// Encoded rows are buffered up to this size before being sent.
+const SEND_THRESHOLD: usize = 64 * 1024;
+
+/// One row of binary `COPY` data, filled by [`BulkInsertable::encode_copy_row`].
+pub struct CopyRow {
+    buf: Vec<u8>,
+    arg: PgArgumentBuffer,
+}
+
+impl CopyRow {
+    fn new() -> Self {
+        Self {
+            buf: Vec::with_capacity(SEND_THRESHOLD),
+            arg: PgArgumentBuffer::default(),
+        }
+    }
+
+    fn start(&mut self, fields: i16) {
+        self.buf.extend_from_slice(&fields.to_be_bytes());
+    }
+
+    /// Appends the next field in the order of `INSERT_COLS`.
+    pub fn push<'q, T>(&mut self, value: &T) -> Result<(), BoxDynError>
+    where
+        T: Encode<'q, Postgres>,
+    {
+        self.arg.clear();
+        match value.encode_by_ref(&mut self.arg)? {
+            IsNull::Yes => self.buf.extend_from_slice(&(-1i32).to_be_bytes()),
+            IsNull::No => {
+                self.buf.extend_from_slice(&i32::try_from(self.arg.len())?.to_be_bytes());
+                self.buf.extend_from_slice(&self.arg);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `COPY "schema"."table" ("a", "b") FROM STDIN (FORMAT binary)` for `T::INSERT_COLS`.
+pub fn copy_in_statement<T: Insertable>() -> String {
+    format!("COPY {} ({}) FROM STDIN (FORMAT binary)", T::QUAL_TABLE, T::INSERT_COLS.join(", "))
+}
+
+/// Streams `rows` into `T`'s table and returns the number of rows written.
+///
+/// The copy is aborted, and nothing is written, if a value fails to encode.
+pub async fn copy_in<'a, T, S, A>(conn: A, rows: S) -> Result<u64, Error>
+where
+    T: BulkInsertable,
+    S: Stream<Item = T>,
+    A: Acquire<'a, Database = Postgres>,
+{
+    let fields = i16::try_from(T::INSERT_COLS.len()).map_err(|e| Error::Encode(e.into()))?;
+    let mut conn = conn.acquire().await?;
+    let mut copy = conn.copy_in_raw(&copy_in_statement::<T>()).await?;
+
+    let mut row = CopyRow::new();
+    row.buf.extend_from_slice(SIGNATURE);
+    row.buf.extend_from_slice(&0i32.to_be_bytes()); // flags
+    row.buf.extend_from_slice(&0i32.to_be_bytes()); // header extension length
+
+    let mut rows = pin!(rows);
+    while let Some(item) = std::future::poll_fn(|cx| rows.as_mut().poll_next(cx)).await {
+        row.start(fields);
+        if let Err(e) = item.encode_copy_row(&mut row) {
+            copy.abort(e.to_string()).await?;
+            return Err(Error::Encode(e));
+        }
+        if row.buf.len() >= SEND_THRESHOLD {
+            copy.send(row.buf.as_slice()).await?;
+            row.buf.clear();
+        }
+    }
+
+    row.buf.extend_from_slice(&(-1i16).to_be_bytes()); // trailer
+    copy.send(row.buf.as_slice()).await?;
+    copy.finish().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_encoding() {
+        let mut row = CopyRow::new();
+        row.start(3);
+        row.push(&7i32).unwrap();
+        row.push(&None::<String>).unwrap();
+        row.push(&"ab").unwrap();
+        assert_eq!(
+            row.buf,
+            [
+                &[0, 3][..],
+                &[0, 0, 0, 4, 0, 0, 0, 7],
+                &[0xff, 0xff, 0xff, 0xff],
+                &[0, 0, 0, 2, b'a', b'b']
+            ]
+            .concat()
+        );
+    }
+}
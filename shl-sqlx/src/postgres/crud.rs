@@ -1,39 +1,356 @@
-use sqlx::{Error, Executor, Postgres};
+use super::composite::ddl_type;
+use super::copy::CopyRow;
+use super::query::Value;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::PgTypeInfo;
+use sqlx::{Acquire, Error, Executor, Postgres};
 
 pub trait TableMeta: Sized {
     const QUAL_TABLE: &'static str;
     const COLS: &'static [&'static str];
     const PK_COLS: &'static [&'static str];
+    /// Quoted `deleted_at`-style column when rows are soft-deleted.
+    const SOFT_DELETE_COL: Option<&'static str> = None;
+    /// Quoted tenant column of `#[table(tenant = "...")]` types.
+    const TENANT_COL: Option<&'static str> = None;
+    /// Quoted version column of `#[table(version = "...")]` types.
+    const VERSION_COL: Option<&'static str> = None;
+    /// Quoted audit table of `#[table(audit)]` types.
+    const AUDIT_TABLE: Option<&'static str> = None;
+    /// Column types in `COLS` order, used to generate DDL.
+    const COLUMN_DEFS: &'static [ColumnDef] = &[];
     type Id;
 }
 
+/// Implemented by `#[derive(Table)]` when the key fields are `Clone`.
+pub trait HasId: TableMeta {
+    /// The primary key fields of `self`, cloned.
+    fn id(&self) -> Self::Id;
+}
+
+/// A column as declared by `#[derive(Table)]`.
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnDef {
+    /// Quoted column name.
+    pub name: &'static str,
+    /// The field's `sqlx::Type`, or `#[table(pg_type = "...")]`.
+    pub type_info: fn() -> PgTypeInfo,
+    /// `Option<_>` field.
+    pub nullable: bool,
+    pub default: Option<&'static str>,
+}
+
+/// `$1::INT8[], $2::jsonb[], ...`: the `UNNEST` arguments of `insert_many` for `columns`.
+#[doc(hidden)]
+pub fn unnest_params<'a>(columns: impl IntoIterator<Item = &'a ColumnDef>) -> String {
+    columns
+        .into_iter()
+        .enumerate()
+        .map(|(i, col)| format!("${}::{}[]", i + 1, ddl_type(&(col.type_info)())))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub trait Readable: TableMeta {
     const SQL_SELECT_BY_PK: &'static str;
     const SQL_DELETE_BY_PK: &'static str;
+    const SQL_DELETE_BY_PK_RETURNING: &'static str;
+    /// `= ANY($1)`, or `IN (SELECT * FROM UNNEST($1, $2, ...))` for composite keys.
+    const SQL_SELECT_BY_PKS: &'static str;
+    const SQL_EXISTS_BY_PK: &'static str;
+    const SQL_COUNT: &'static str;
 
     fn find_by_id<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<Self, Error>> + Send + 'e
     where
         Self: Sized + 'e,
         E: Executor<'e, Database = Postgres> + Send + 'e;
 
+    fn find_optional_by_id<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<Option<Self>, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    /// Rows are returned in no particular order; missing ids are skipped.
+    fn find_by_ids<'e, E>(exec: E, ids: &'e [Self::Id]) -> impl Future<Output = Result<Vec<Self>, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn exists_by_id<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<bool, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn count<'e, E>(exec: E) -> impl Future<Output = Result<i64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
     fn delete_by_id<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<u64, Error>> + Send + 'e
     where
         E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn delete_returning<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+}
+
+/// Generated by `#[derive(Table)]` with `#[table(soft_delete = "...")]`, in which case
+/// `Readable::delete_by_id` only marks the row and `Readable::find_by_id` skips marked rows.
+pub trait SoftDeletable: Readable {
+    const SQL_SELECT_BY_PK_WITH_DELETED: &'static str;
+    const SQL_RESTORE_BY_PK: &'static str;
+    const SQL_PURGE_BY_PK: &'static str;
+
+    fn find_by_id_with_deleted<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn restore_by_id<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    /// Deletes the row for good.
+    fn purge_by_id<'e, E>(exec: E, id: Self::Id) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
 }
 
 pub trait Insertable: TableMeta {
     const INSERT_COLS: &'static [&'static str];
     const SQL_INSERT: &'static str;
+    const SQL_INSERT_RETURNING: &'static str;
 
     fn insert<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<u64, Error>> + Send + 'e
     where
         E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn insert_returning<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+}
+
+/// Bulk inserts of [`Insertable`] rows, implemented by `#[derive(Insertable)]` for
+/// `#[table(bulk)]` types. They write no audit rows, so `bulk` is rejected on `#[table(audit)]` types.
+pub trait BulkInsertable: Insertable {
+    /// `INSERT ... SELECT ... FROM UNNEST($1::INT8[], $2::jsonb[], ...)` with one array parameter
+    /// per column, cast to the type of its [`ColumnDef`]. Built on first use, as the types of
+    /// declared enums and `jsonb` fields are only known at runtime.
+    ///
+    /// Every field is bound as an array, so its type must implement `PgHasArrayType`, as the
+    /// `impl_*!` macros and the `PgEnum`/`PgJsonb`/`PgComposite` derives do.
+    fn sql_insert_many() -> &'static str;
+    /// Rows sent per `insert_many` statement. The parameter count does not grow with the rows,
+    /// so this only bounds the size of a single statement.
+    const INSERT_MANY_CHUNK: usize = 5_000;
+
+    /// Inserts all `rows` in one round trip per `INSERT_MANY_CHUNK`, inside a transaction
+    /// (a savepoint when `conn` already is one).
+    fn insert_many<'a, A>(conn: A, rows: &'a [Self]) -> impl Future<Output = Result<u64, Error>> + Send + 'a
+    where
+        A: Acquire<'a, Database = Postgres> + Send + 'a;
+
+    /// Writes the `INSERT_COLS` fields of `self` for [`copy_in`](super::copy::copy_in).
+    fn encode_copy_row(&self, row: &mut CopyRow) -> Result<(), BoxDynError>;
+}
+
+pub trait Upsertable: Insertable {
+    const CONFLICT_COLS: &'static [&'static str];
+    const CONFLICT_UPDATE_COLS: &'static [&'static str];
+    const SQL_UPSERT: &'static str;
+    const SQL_INSERT_OR_IGNORE: &'static str;
+
+    fn upsert<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn insert_or_ignore<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
 }
 
 pub trait Updatable: TableMeta {
+    /// `sqlx::Error`, or [`UpdateError`](super::UpdateError) for `#[table(version = "...")]` types.
+    type Error: From<Error>;
+    /// Generated `<Name>Patch` with every updatable field wrapped in `Option`.
+    type Patch: Default + Send;
+    /// `()`, or the type of the version column for `#[table(version = "...")]` types.
+    type Version: Send;
+
     const SQL_UPDATE: &'static str;
+    const SQL_UPDATE_RETURNING: &'static str;
+
+    /// With a version column the row must still hold `self`'s version; the stored version is
+    /// incremented, so use `update_returning` to keep working with the same value.
+    fn update<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<u64, Self::Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn update_returning<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<Self, Self::Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    /// Writes only the `Some` fields of `patch` (plus `auto_now_update` and version columns).
+    /// With a version column the row must still hold `version`, like [`update`](Self::update);
+    /// pass `()` otherwise.
+    ///
+    /// An empty patch does not touch the database and returns `Ok(0)`, the same as a missing row;
+    /// check the generated `<Name>Patch::is_empty` first when the two must be told apart.
+    fn update_patch<'e, E>(
+        exec: E,
+        id: Self::Id,
+        version: Self::Version,
+        patch: Self::Patch,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+}
+
+/// Implemented by `#[derive(Updatable)]` when the updated fields are `Clone`.
+pub trait CopyUpdated: Updatable {
+    /// Copies the fields [`Updatable::update`] binds from `self` into `row`. The key, version,
+    /// `skip_update` and `auto_now` fields of `row` are kept.
+    fn copy_updated(&self, row: &mut Self);
+}
+
+/// Implemented by `#[derive(Table)]` with `#[table(tenant = "...")]`.
+///
+/// Such types get [`TenantReadable`], [`TenantInsertable`] and [`TenantUpdatable`] instead of the
+/// unscoped traits: every statement takes the tenant as an argument and includes
+/// `AND "tenant_id" = $n`, so rows of another tenant can't be reached by forgetting a filter.
+/// [`Select`](super::query::Select) matches no rows until [`tenant`](super::query::Select::tenant) is set.
+pub trait TenantScoped: TableMeta {
+    type Tenant: Value;
+}
+
+/// [`Readable`] scoped to a tenant.
+pub trait TenantReadable: TenantScoped {
+    const SQL_SELECT_BY_PK: &'static str;
+    const SQL_DELETE_BY_PK: &'static str;
+    const SQL_DELETE_BY_PK_RETURNING: &'static str;
+    const SQL_SELECT_BY_PKS: &'static str;
+    const SQL_EXISTS_BY_PK: &'static str;
+    const SQL_COUNT: &'static str;
+
+    fn find_by_id<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn find_optional_by_id<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<Option<Self>, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    /// Rows are returned in no particular order; missing ids and rows of other tenants are skipped.
+    fn find_by_ids<'e, E>(exec: E, tenant: Self::Tenant, ids: &'e [Self::Id]) -> impl Future<Output = Result<Vec<Self>, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn exists_by_id<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<bool, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn count<'e, E>(exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<i64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn delete_by_id<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn delete_returning<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+}
+
+/// [`SoftDeletable`] scoped to a tenant.
+pub trait TenantSoftDeletable: TenantReadable {
+    const SQL_SELECT_BY_PK_WITH_DELETED: &'static str;
+    const SQL_RESTORE_BY_PK: &'static str;
+    const SQL_PURGE_BY_PK: &'static str;
+
+    fn find_by_id_with_deleted<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        Self: Sized + 'e,
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn restore_by_id<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn purge_by_id<'e, E>(exec: E, tenant: Self::Tenant, id: Self::Id) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+}
+
+/// [`Insertable`] and [`Upsertable`] scoped to a tenant: the tenant column is written from the
+/// `tenant` argument rather than the field, and an upsert never overwrites another tenant's row.
+pub trait TenantInsertable: TenantScoped {
+    const INSERT_COLS: &'static [&'static str];
+    const SQL_INSERT: &'static str;
+    const SQL_INSERT_RETURNING: &'static str;
+    const CONFLICT_COLS: &'static [&'static str];
+    const CONFLICT_UPDATE_COLS: &'static [&'static str];
+    const SQL_UPSERT: &'static str;
+    const SQL_INSERT_OR_IGNORE: &'static str;
+
+    fn insert<'e, E>(&'e self, exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn insert_returning<'e, E>(&'e self, exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<Self, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    /// Returns `Ok(0)` when the conflicting row belongs to another tenant.
+    fn upsert<'e, E>(&'e self, exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn insert_or_ignore<'e, E>(&'e self, exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+}
+
+/// [`BulkInsertable`] scoped to a tenant, also implemented for `#[table(bulk)]` types only.
+pub trait TenantBulkInsertable: TenantInsertable {
+    /// See [`BulkInsertable::sql_insert_many`]; the tenant is bound once as the last parameter,
+    /// after one array per column.
+    fn sql_insert_many() -> &'static str;
+    const INSERT_MANY_CHUNK: usize = 5_000;
+
+    fn insert_many<'a, A>(conn: A, tenant: Self::Tenant, rows: &'a [Self]) -> impl Future<Output = Result<u64, Error>> + Send + 'a
+    where
+        A: Acquire<'a, Database = Postgres> + Send + 'a;
+}
+
+/// [`Updatable`] scoped to a tenant. The tenant column itself is never updated.
+pub trait TenantUpdatable: TenantScoped {
+    type Error: From<Error>;
+    type Patch: Default + Send;
+    type Version: Send;
+
+    const SQL_UPDATE: &'static str;
+    const SQL_UPDATE_RETURNING: &'static str;
+
+    fn update<'e, E>(&'e self, exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<u64, Self::Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
+
+    fn update_returning<'e, E>(&'e self, exec: E, tenant: Self::Tenant) -> impl Future<Output = Result<Self, Self::Error>> + Send + 'e
+    where
+        E: Executor<'e, Database = Postgres> + Send + 'e;
 
-    fn update<'e, E>(&'e self, exec: E) -> impl Future<Output = Result<u64, Error>> + Send + 'e
+    fn update_patch<'e, E>(
+        exec: E,
+        tenant: Self::Tenant,
+        id: Self::Id,
+        version: Self::Version,
+        patch: Self::Patch,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send + 'e
     where
         E: Executor<'e, Database = Postgres> + Send + 'e;
 }
@@ -0,0 +1,260 @@
+//! Column-level encryption for `#[table(encrypted)]` fields.
+//!
+//! [`Encrypted<T>`] serializes the value with `serde_json` and encrypts it with AES-256-GCM on
+//! `Encode`, decrypting on `Decode`. Values are stored in `text` columns as
+//! `<key id>:<base64 of nonce and ciphertext>`, the key id also being authenticated as associated
+//! data. New values always use [`KeyProvider::current_key_id`]; older rows stay readable as long as
+//! the provider still returns their key, so a key is rotated by switching the current id and
+//! re-saving the rows still starting with the old prefix (`WHERE "email" LIKE 'k1:%'`).
+//!
+//! ```ignore
+//! set_key_provider(StaticKeyProvider::new("k2").with_key("k1", old_key).with_key("k2", new_key));
+//!
+//! #[derive(FromRow, Table, Insertable, Updatable)]
+//! pub struct User {
+//!     pub id: Uuid,
+//!     #[table(encrypted)]
+//!     pub email: Option<Encrypted<String>>,
+//! }
+//! ```
+//!
+//! Ciphertexts differ on every write, so encrypted columns can't be filtered on or used as keys.
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres, Type};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock};
+
+/// AES-256 key.
+pub type Key = [u8; 32];
+
+/// Supplies the keys used by [`Encrypted`], e.g. from a secret manager or KMS.
+pub trait KeyProvider: Send + Sync {
+    /// Id of the key new values are encrypted with. Must not contain `:`.
+    fn current_key_id(&self) -> String;
+
+    /// The key stored values prefixed with `key_id` were encrypted with.
+    fn key(&self, key_id: &str) -> Option<Key>;
+}
+
+/// A fixed set of keys, typically loaded from configuration at startup.
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    current: String,
+    keys: HashMap<String, Key>,
+}
+
+impl StaticKeyProvider {
+    pub fn new(current_key_id: impl Into<String>) -> Self {
+        Self {
+            current: current_key_id.into(),
+            keys: HashMap::new(),
+        }
+    }
+
+    pub fn with_key(mut self, key_id: impl Into<String>, key: Key) -> Self {
+        self.keys.insert(key_id.into(), key);
+        self
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key_id(&self) -> String {
+        self.current.clone()
+    }
+
+    fn key(&self, key_id: &str) -> Option<Key> {
+        self.keys.get(key_id).copied()
+    }
+}
+
+static PROVIDER: RwLock<Option<Arc<dyn KeyProvider>>> = RwLock::new(None);
+
+/// Installs the provider used by every [`Encrypted`] value, replacing the previous one.
+pub fn set_key_provider(provider: impl KeyProvider + 'static) {
+    *PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(provider));
+}
+
+fn provider() -> Result<Arc<dyn KeyProvider>, EncryptionError> {
+    PROVIDER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or(EncryptionError::NoKeyProvider)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EncryptionError {
+    #[error("no key provider set, call shl_sqlx::postgres::encrypted::set_key_provider first")]
+    NoKeyProvider,
+    #[error("unknown encryption key '{0}'")]
+    UnknownKey(String),
+    #[error("invalid key id '{0}'")]
+    InvalidKeyId(String),
+    #[error("malformed encrypted value")]
+    Malformed,
+    #[error("generating a nonce failed")]
+    Rng,
+    #[error("encryption failed")]
+    Encrypt,
+    #[error("decryption failed")]
+    Decrypt,
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+fn cipher(key: &Key) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).expect("AES-256 key is 32 bytes"))
+}
+
+/// Encrypts `plaintext` with the current key into the stored `key_id:base64` form.
+pub fn encrypt(plaintext: &[u8]) -> Result<String, EncryptionError> {
+    let provider = provider()?;
+    let key_id = provider.current_key_id();
+    if key_id.is_empty() || key_id.contains(':') {
+        return Err(EncryptionError::InvalidKeyId(key_id));
+    }
+    let key = provider.key(&key_id).ok_or_else(|| EncryptionError::UnknownKey(key_id.clone()))?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce).map_err(|_| EncryptionError::Rng)?;
+    let mut buf = plaintext.to_vec();
+    cipher(&key)
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(key_id.as_bytes()), &mut buf)
+        .map_err(|_| EncryptionError::Encrypt)?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&buf);
+    Ok(format!("{}:{}", key_id, STANDARD.encode(payload)))
+}
+
+/// Reverses [`encrypt`] with the key named by the value's prefix.
+pub fn decrypt(stored: &str) -> Result<Vec<u8>, EncryptionError> {
+    let (key_id, payload) = stored.split_once(':').ok_or(EncryptionError::Malformed)?;
+    let key = provider()?.key(key_id).ok_or_else(|| EncryptionError::UnknownKey(key_id.to_owned()))?;
+    let payload = STANDARD.decode(payload).map_err(|_| EncryptionError::Malformed)?;
+    if payload.len() < NONCE_LEN {
+        return Err(EncryptionError::Malformed);
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| EncryptionError::Malformed)?;
+    let mut buf = ciphertext.to_vec();
+    let plaintext = cipher(&key)
+        .open_in_place(nonce, Aad::from(key_id.as_bytes()), &mut buf)
+        .map_err(|_| EncryptionError::Decrypt)?;
+    Ok(plaintext.to_vec())
+}
+
+/// Key id prefix of a stored value.
+pub fn key_id(stored: &str) -> Option<&str> {
+    stored.split_once(':').map(|(id, _)| id)
+}
+
+/// A value encrypted in the database and plain in memory.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Encrypted<T>(pub T);
+
+impl<T> Encrypted<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Encrypted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Encrypted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Encrypted<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// Redacted, so the plaintext doesn't end up in logs.
+impl<T> fmt::Debug for Encrypted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Encrypted(..)")
+    }
+}
+
+impl<T> Type<Postgres> for Encrypted<T> {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+/// Lets `insert_many` bind encrypted columns as `text[]`.
+impl<T> PgHasArrayType for Encrypted<T> {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<'q, T: Serialize> Encode<'q, Postgres> for Encrypted<T> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        let stored = encrypt(&serde_json::to_vec(&self.0)?)?;
+        <String as Encode<Postgres>>::encode(stored, buf)
+    }
+}
+
+impl<'r, T: DeserializeOwned> Decode<'r, Postgres> for Encrypted<T> {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        let stored = <&str as Decode<Postgres>>::decode(value)?;
+        let plaintext = decrypt(stored)?;
+        Ok(Self(serde_json::from_slice(&plaintext)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        set_key_provider(StaticKeyProvider::new("k1").with_key("k1", [1; 32]));
+        let old = encrypt(b"secret").unwrap();
+        assert_eq!(key_id(&old), Some("k1"));
+        assert_ne!(encrypt(b"secret").unwrap(), old, "nonces must differ");
+
+        set_key_provider(StaticKeyProvider::new("k2").with_key("k1", [1; 32]).with_key("k2", [2; 32]));
+        let new = encrypt(b"secret").unwrap();
+        assert!(new.starts_with("k2:"));
+        assert_eq!(decrypt(&old).unwrap(), b"secret");
+        assert_eq!(decrypt(&new).unwrap(), b"secret");
+
+        // the key id is authenticated
+        let forged = format!("k1:{}", new.strip_prefix("k2:").unwrap());
+        assert!(matches!(decrypt(&forged), Err(EncryptionError::Decrypt)));
+
+        set_key_provider(StaticKeyProvider::new("k2").with_key("k2", [2; 32]));
+        assert!(matches!(decrypt(&old), Err(EncryptionError::UnknownKey(id)) if id == "k1"));
+        assert!(matches!(decrypt("garbage"), Err(EncryptionError::Malformed)));
+    }
+}
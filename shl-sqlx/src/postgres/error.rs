@@ -0,0 +1,113 @@
+use sqlx::error::DatabaseError;
+use sqlx::postgres::PgDatabaseError;
+
+/// Returned by `Updatable::update` of `#[table(version = "...")]` types when no row matched
+/// the expected version, i.e. the row was changed or deleted concurrently.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("stale version {version} of row in {table}")]
+pub struct StaleVersion {
+    pub table: &'static str,
+    pub version: i64,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum UpdateError {
+    #[error(transparent)]
+    StaleVersion(#[from] StaleVersion),
+
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+}
+
+/// Returned by the `FromStr` impl of `#[derive(PgEnum)]` types.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown {type_name} variant '{value}'")]
+pub struct ParseEnumError {
+    pub type_name: &'static str,
+    pub value: String,
+}
+
+/// Returned by the `PgJsonb` conversions instead of panicking or storing `null`.
+#[derive(thiserror::Error, Debug)]
+pub enum JsonbError {
+    #[error("invalid {type_name} JSON: {source}")]
+    Json {
+        type_name: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("{type_name} version {version} is newer than the supported version {current}")]
+    UnsupportedVersion { type_name: &'static str, version: u32, current: u32 },
+
+    #[error("no upgrade registered for {type_name} version {version}")]
+    MissingUpgrade { type_name: &'static str, version: u32 },
+
+    #[error("upgrading {type_name} from version {version} failed: {message}")]
+    Upgrade {
+        type_name: &'static str,
+        version: u32,
+        message: String,
+    },
+}
+
+/// A `sqlx::Error` classified by the Postgres SQLSTATE, so callers can match on constraint
+/// violations instead of inspecting `DatabaseError::code()`.
+///
+/// With the `ntex` feature it is an `NtexError`: constraint violations map to 409/422, `NotFound`
+/// to 404 and everything else to a 500 that doesn't expose the underlying error.
+#[derive(thiserror::Error, Debug)]
+#[cfg_attr(feature = "ntex", derive(shl_ntex::error::NtexError))]
+pub enum DbError {
+    #[error("unique constraint {constraint} violated")]
+    #[cfg_attr(feature = "ntex", ntex_response(status = "CONFLICT"))]
+    UniqueViolation { constraint: String },
+
+    #[error("foreign key constraint {constraint} violated")]
+    #[cfg_attr(feature = "ntex", ntex_response(status = "CONFLICT"))]
+    ForeignKeyViolation { constraint: String },
+
+    #[error("column {column} must not be null")]
+    #[cfg_attr(feature = "ntex", ntex_response(status = "UNPROCESSABLE_ENTITY"))]
+    NotNullViolation { column: String },
+
+    #[error("check constraint {constraint} violated")]
+    #[cfg_attr(feature = "ntex", ntex_response(status = "UNPROCESSABLE_ENTITY"))]
+    CheckViolation { constraint: String },
+
+    #[error("not found")]
+    #[cfg_attr(feature = "ntex", ntex_response(status = "NOT_FOUND"))]
+    NotFound,
+
+    #[error("database error")]
+    #[cfg_attr(feature = "ntex", ntex_response(skip_fields))]
+    Other(#[source] sqlx::Error),
+}
+
+impl From<sqlx::Error> for DbError {
+    fn from(e: sqlx::Error) -> Self {
+        let sqlx::Error::Database(db) = &e else {
+            return match e {
+                sqlx::Error::RowNotFound => Self::NotFound,
+                e => Self::Other(e),
+            };
+        };
+        let constraint = || db.constraint().unwrap_or_default().to_owned();
+        match db.code().as_deref() {
+            Some("23505") => Self::UniqueViolation { constraint: constraint() },
+            Some("23503") => Self::ForeignKeyViolation { constraint: constraint() },
+            Some("23514") => Self::CheckViolation { constraint: constraint() },
+            Some("23502") => Self::NotNullViolation {
+                column: not_null_column(db.as_ref()),
+            },
+            _ => Self::Other(e),
+        }
+    }
+}
+
+fn not_null_column(db: &dyn DatabaseError) -> String {
+    db.try_downcast_ref::<PgDatabaseError>()
+        .and_then(|e| e.column())
+        .unwrap_or_default()
+        .to_owned()
+}
@@ -0,0 +1,155 @@
+use super::JsonbError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Key of the schema version in the stored envelope.
+pub const VERSION_KEY: &str = "$v";
+/// Key of the payload in the stored envelope.
+pub const DATA_KEY: &str = "$data";
+
+/// Converts a payload of one schema version to the next.
+pub type Upgrade = fn(Value) -> Result<Value, String>;
+
+/// Implemented by `impl_serde_jsonb!` and `#[derive(PgJsonb)]` for serde types stored as `jsonb`,
+/// together with `TryFrom` conversions from and to `serde_json::Value` and the sqlx
+/// `Type`/`Encode`/`Decode` traits.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, PgJsonb)]
+/// #[pg_jsonb(version = 2, upgrade(from = 0, with = "settings_v0"), upgrade(from = 1, with = "settings_v1"))]
+/// pub struct Settings {
+///     pub theme: String,
+///     pub notifications: Notifications,
+/// }
+///
+/// fn settings_v0(mut json: Value) -> Result<Value, String> {
+///     json["theme"] = json.get("dark").map(|d| if d == true { "dark" } else { "light" }).into();
+///     Ok(json)
+/// }
+/// ```
+///
+/// Versioned types are stored as `{"$v": 2, "$data": {...}}`. Values without the envelope, e.g.
+/// rows written before a version was declared, are version 0. On read the `UPGRADES` are applied
+/// one version at a time up to `VERSION` before deserializing; values written by a newer version
+/// are rejected rather than guessed at.
+pub trait PgJsonb: Serialize + DeserializeOwned {
+    /// Current schema version, `None` to store the bare value.
+    const VERSION: Option<u32> = None;
+    /// `(from, upgrade)` pairs, each converting a `from` payload to `from + 1`.
+    const UPGRADES: &'static [(u32, Upgrade)] = &[];
+
+    fn to_jsonb(&self) -> Result<Value, JsonbError> {
+        let data = serde_json::to_value(self).map_err(|source| JsonbError::Json {
+            type_name: std::any::type_name::<Self>(),
+            source,
+        })?;
+        Ok(match Self::VERSION {
+            Some(version) => Value::Object(Map::from_iter([
+                (VERSION_KEY.to_owned(), Value::from(version)),
+                (DATA_KEY.to_owned(), data),
+            ])),
+            None => data,
+        })
+    }
+
+    fn from_jsonb(json: Value) -> Result<Self, JsonbError> {
+        let type_name = std::any::type_name::<Self>();
+        let mut data = json;
+        if let Some(current) = Self::VERSION {
+            let (mut version, payload) = split_envelope(data);
+            data = payload;
+            if version > current {
+                return Err(JsonbError::UnsupportedVersion { type_name, version, current });
+            }
+            while version < current {
+                let Some((_, upgrade)) = Self::UPGRADES.iter().find(|(from, _)| *from == version) else {
+                    return Err(JsonbError::MissingUpgrade { type_name, version });
+                };
+                data = upgrade(data).map_err(|message| JsonbError::Upgrade { type_name, version, message })?;
+                version += 1;
+            }
+        }
+        serde_json::from_value(data).map_err(|source| JsonbError::Json { type_name, source })
+    }
+}
+
+/// `(version, payload)` of an enveloped value, `(0, json)` for anything else.
+fn split_envelope(json: Value) -> (u32, Value) {
+    match json {
+        Value::Object(mut map)
+            if map.len() == 2
+                && map.contains_key(DATA_KEY)
+                && let Some(version) = map.get(VERSION_KEY).and_then(Value::as_u64).and_then(|v| u32::try_from(v).ok()) =>
+        {
+            (version, map.remove(DATA_KEY).unwrap_or_default())
+        }
+        other => (0, other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Plain {
+        name: String,
+    }
+    crate::impl_serde_jsonb!(Plain);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        theme: String,
+        size: u32,
+    }
+    crate::impl_serde_jsonb!(Settings, version = 2, upgrades = { 0 => settings_v0, 1 => settings_v1 });
+
+    fn settings_v0(json: Value) -> Result<Value, String> {
+        let dark = json.get("dark").and_then(Value::as_bool).ok_or("missing dark")?;
+        Ok(json!({ "theme": if dark { "dark" } else { "light" } }))
+    }
+
+    fn settings_v1(mut json: Value) -> Result<Value, String> {
+        json["size"] = json!(12);
+        Ok(json)
+    }
+
+    #[test]
+    fn test_try_from() {
+        let plain = Plain { name: "a".to_owned() };
+        assert_eq!(Value::try_from(plain).unwrap(), json!({ "name": "a" }));
+        let err = Plain::try_from(json!({ "title": "a" })).unwrap_err();
+        assert!(matches!(err, JsonbError::Json { .. }));
+        assert!(err.to_string().contains("Plain"));
+    }
+
+    #[test]
+    fn test_versions() {
+        let settings = Settings {
+            theme: "dark".to_owned(),
+            size: 14,
+        };
+        let stored = settings.to_jsonb().unwrap();
+        assert_eq!(stored, json!({ "$v": 2, "$data": { "theme": "dark", "size": 14 } }));
+        assert_eq!(Settings::from_jsonb(stored).unwrap(), settings);
+
+        let upgraded = Settings {
+            theme: "dark".to_owned(),
+            size: 12,
+        };
+        assert_eq!(Settings::from_jsonb(json!({ "dark": true })).unwrap(), upgraded);
+        assert_eq!(Settings::from_jsonb(json!({ "$v": 1, "$data": { "theme": "dark" } })).unwrap(), upgraded);
+
+        assert!(matches!(
+            Settings::from_jsonb(json!({ "$v": 3, "$data": {} })),
+            Err(JsonbError::UnsupportedVersion { version: 3, current: 2, .. })
+        ));
+        assert!(matches!(
+            Settings::from_jsonb(json!({ "theme": "dark" })),
+            Err(JsonbError::Upgrade { version: 0, .. })
+        ));
+    }
+}
@@ -1,28 +1,59 @@
+/// Implements [`PgJsonb`](crate::postgres::PgJsonb), `TryFrom` conversions from and to
+/// `serde_json::Value` and the sqlx traits for a serde type stored as `jsonb` (or `json`).
+///
+/// ```ignore
+/// impl_serde_jsonb!(Settings);
+/// impl_serde_jsonb!(Settings, "json");
+/// impl_serde_jsonb!(Settings, version = 2, upgrades = { 0 => settings_v0, 1 => settings_v1 });
+/// ```
 #[macro_export]
 macro_rules! impl_serde_jsonb {
-    ($(#[$attr:meta])* $name:ident, $type:literal) => {
+    (@type) => { "jsonb" };
+    (@type $type:literal) => { $type };
+    (@version) => { None };
+    (@version $version:literal) => { Some($version) };
+    (
+        $(#[$attr:meta])* $name:ident
+        $(, $type:literal)?
+        $(, version = $version:literal $(, upgrades = { $($from:literal => $upgrade:path),* $(,)? })?)?
+    ) => {
         $(#[$attr])*
-        impl From<$name> for serde_json::Value {
-            fn from(value: $name) -> Self {
-                serde_json::to_value(&value).unwrap_or(serde_json::Value::Null)
+        impl $crate::postgres::PgJsonb for $name {
+            const VERSION: Option<u32> = $crate::impl_serde_jsonb!(@version $($version)?);
+            const UPGRADES: &'static [(u32, $crate::postgres::Upgrade)] = &[
+                $($($( ($from, $upgrade as $crate::postgres::Upgrade), )*)?)?
+            ];
+        }
+
+        $(#[$attr])*
+        impl TryFrom<$name> for serde_json::Value {
+            type Error = $crate::postgres::JsonbError;
+
+            fn try_from(value: $name) -> Result<Self, Self::Error> {
+                $crate::postgres::PgJsonb::to_jsonb(&value)
             }
         }
 
         $(#[$attr])*
-        impl From<serde_json::Value> for $name {
-            fn from(json: serde_json::Value) -> Self {
-                serde_json::from_value(json).expect(concat!(
-                    "Failed to deserialize ",
-                    stringify!($name),
-                    " from JsonValue"
-                ))
+        impl TryFrom<serde_json::Value> for $name {
+            type Error = $crate::postgres::JsonbError;
+
+            fn try_from(json: serde_json::Value) -> Result<Self, Self::Error> {
+                <$name as $crate::postgres::PgJsonb>::from_jsonb(json)
             }
         }
 
         $(#[$attr])*
         impl sqlx::Type<sqlx::Postgres> for $name {
             fn type_info() -> sqlx::postgres::PgTypeInfo {
-                sqlx::postgres::PgTypeInfo::with_name($type)
+                sqlx::postgres::PgTypeInfo::with_name($crate::impl_serde_jsonb!(@type $($type)?))
+            }
+        }
+
+        $(#[$attr])*
+        impl sqlx::postgres::PgHasArrayType for $name {
+            fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+                sqlx::postgres::PgTypeInfo::array_of($crate::impl_serde_jsonb!(@type $($type)?))
             }
         }
 
@@ -32,14 +63,8 @@ macro_rules! impl_serde_jsonb {
                 &self,
                 buf: &mut <sqlx::Postgres as sqlx::Database>::ArgumentBuffer<'q>,
             ) -> Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync>> {
-                let json_value = match serde_json::to_value(self) {
-                    Ok(value) => value,
-                    Err(err) => return Err(Box::new(err)),
-                };
-                <serde_json::Value as sqlx::encode::Encode<sqlx::Postgres>>::encode_by_ref(
-                    &json_value,
-                    buf,
-                )
+                let json_value = $crate::postgres::PgJsonb::to_jsonb(self)?;
+                <serde_json::Value as sqlx::encode::Encode<sqlx::Postgres>>::encode_by_ref(&json_value, buf)
             }
         }
 
@@ -48,17 +73,9 @@ macro_rules! impl_serde_jsonb {
             fn decode(
                 value: sqlx::postgres::PgValueRef<'r>,
             ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-                let json_value =
-                    <serde_json::Value as sqlx::decode::Decode<sqlx::Postgres>>::decode(value)?;
-                serde_json::from_value(json_value)
-                    .map_err(|e| {
-                        let err_msg = format!("Failed to decode {} from JSON: {}", stringify!($name), e);
-                        Box::<dyn std::error::Error + Send + Sync>::from(err_msg)
-                    })
+                let json_value = <serde_json::Value as sqlx::decode::Decode<sqlx::Postgres>>::decode(value)?;
+                Ok(<$name as $crate::postgres::PgJsonb>::from_jsonb(json_value)?)
             }
         }
     };
-    ($(#[$attr:meta])* $name:ident) => {
-        impl_serde_jsonb!($(#[$attr])* $name, "jsonb");
-    };
 }
@@ -7,6 +7,12 @@ macro_rules! impl_to_string_varchar {
             }
         }
 
+        impl sqlx::postgres::PgHasArrayType for $name {
+            fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+                sqlx::postgres::PgTypeInfo::with_name("_varchar")
+            }
+        }
+
         impl<'q> sqlx::encode::Encode<'q, sqlx::Postgres> for $name {
             fn encode_by_ref(
                 &self,
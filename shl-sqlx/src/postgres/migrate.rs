@@ -0,0 +1,237 @@
+//! `CREATE TABLE` / `ALTER TABLE` generation from `#[derive(Table)]` types.
+//!
+//! ```ignore
+//! let schema = Schema::new().register::<User>().register::<Integration>();
+//! println!("{}", schema.create_sql());
+//! // or, against a database migrated up to the previous release:
+//! println!("{}", schema.diff(&pool).await?);
+//! ```
+//!
+//! Column types come from the fields' `sqlx::Type` (or `#[table(pg_type = "...")]`), `NOT NULL`
+//! from non-`Option` fields and the primary key from `PK_COLS`. The output is meant to be
+//! reviewed and committed as a migration; columns missing from the structs are only reported.
+use super::schema::{ColumnSchema, Snapshot};
+use super::{ColumnDef, TableMeta};
+use sqlx::{Error, Executor, Postgres};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy)]
+struct TableDef {
+    qual_table: &'static str,
+    pk_cols: &'static [&'static str],
+    columns: &'static [ColumnDef],
+}
+
+impl TableDef {
+    /// `schema.table`, the key of [`Snapshot::tables`].
+    fn key(&self) -> String {
+        self.qual_table.replace('"', "")
+    }
+
+    fn schema(&self) -> String {
+        self.key().split('.').next().unwrap_or_default().to_owned()
+    }
+}
+
+/// The set of tables a migration is generated for.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    tables: Vec<TableDef>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: TableMeta>(mut self) -> Self {
+        self.tables.push(TableDef {
+            qual_table: T::QUAL_TABLE,
+            pk_cols: T::PK_COLS,
+            columns: T::COLUMN_DEFS,
+        });
+        self
+    }
+
+    /// `CREATE TABLE` statements for every registered table.
+    pub fn create_sql(&self) -> String {
+        self.tables.iter().map(create_table).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Statements turning `existing` into the registered tables.
+    pub fn diff_sql(&self, existing: &Snapshot) -> String {
+        let mut out = Vec::new();
+        for table in &self.tables {
+            match existing.tables.get(&table.key()) {
+                Some(cols) => out.extend(alter_table(table, cols)),
+                None => out.push(create_table(table)),
+            }
+        }
+        out.join("\n")
+    }
+
+    /// Reads the current schema from `information_schema` and returns [`Schema::diff_sql`],
+    /// empty when the database is up to date.
+    pub async fn diff<'e, E>(&self, exec: E) -> Result<String, Error>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let schemas = self.tables.iter().map(TableDef::schema).collect::<BTreeSet<_>>();
+        let schemas = schemas.iter().map(String::as_str).collect::<Vec<_>>();
+        let existing = Snapshot::fetch(exec, &schemas).await?;
+        Ok(self.diff_sql(&existing))
+    }
+}
+
+fn ddl_type(col: &ColumnDef) -> String {
+    super::composite::ddl_type(&(col.type_info)())
+}
+
+/// Normalizes a DDL type or `udt_name` for comparison, e.g. `TEXT[]` and `_text`. `udt_name` has
+/// no schema, so one is dropped from declared types like `app.state`.
+fn udt_name(ty: &str) -> String {
+    let ty = ty.to_lowercase();
+    let (ty, array) = match ty.strip_suffix("[]") {
+        Some(elem) => (elem, true),
+        None => (ty.as_str(), false),
+    };
+    let name = ty.rsplit('.').next().unwrap_or_default().trim_matches('"');
+    match array {
+        true => format!("_{}", name),
+        false => name.to_owned(),
+    }
+}
+
+fn same_type(declared: &str, udt: &str) -> bool {
+    const TEXT: &[&str] = &["text", "varchar", "bpchar", "citext", "name"];
+    const JSON: &[&str] = &["json", "jsonb"];
+    let declared = udt_name(declared);
+    let element = |t: &str| t.strip_prefix('_').map(str::to_owned).unwrap_or_else(|| t.to_owned());
+    let same_family = |family: &[&str]| {
+        declared.starts_with('_') == udt.starts_with('_') && family.contains(&element(&declared).as_str()) && family.contains(&element(udt).as_str())
+    };
+    declared == udt || same_family(TEXT) || same_family(JSON)
+}
+
+fn column_sql(col: &ColumnDef) -> String {
+    let mut sql = format!("{} {}", col.name, ddl_type(col));
+    if !col.nullable {
+        sql.push_str(" NOT NULL");
+    }
+    if let Some(default) = col.default {
+        sql.push_str(" DEFAULT ");
+        sql.push_str(default);
+    }
+    sql
+}
+
+fn create_table(table: &TableDef) -> String {
+    let mut lines = table.columns.iter().map(column_sql).collect::<Vec<_>>();
+    if !table.pk_cols.is_empty() {
+        lines.push(format!("PRIMARY KEY ({})", table.pk_cols.join(", ")));
+    }
+    format!("CREATE TABLE {} (\n    {}\n);", table.qual_table, lines.join(",\n    "))
+}
+
+fn alter_table(table: &TableDef, existing: &[ColumnSchema]) -> Vec<String> {
+    let alter = |action: String| format!("ALTER TABLE {} {};", table.qual_table, action);
+    let mut out = Vec::new();
+    for col in table.columns {
+        let name = col.name.trim_matches('"');
+        let Some(current) = existing.iter().find(|c| c.name == name) else {
+            out.push(alter(format!("ADD COLUMN {}", column_sql(col))));
+            continue;
+        };
+        if !same_type(&ddl_type(col), &current.pg_type) {
+            out.push(alter(format!("ALTER COLUMN {0} TYPE {1} USING {0}::{1}", col.name, ddl_type(col))));
+        }
+        if current.nullable && !col.nullable {
+            out.push(alter(format!("ALTER COLUMN {} SET NOT NULL", col.name)));
+        } else if !current.nullable && col.nullable {
+            out.push(alter(format!("ALTER COLUMN {} DROP NOT NULL", col.name)));
+        }
+    }
+    for current in existing {
+        if !table.columns.iter().any(|c| c.name.trim_matches('"') == current.name) {
+            out.push(format!("-- {}", alter(format!("DROP COLUMN \"{}\"", current.name))));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as shl_sqlx;
+    use shl_sqlx::Table;
+    use std::collections::BTreeMap;
+
+    #[derive(sqlx::FromRow, Table)]
+    #[allow(dead_code)]
+    #[table(schema = "app", pk("tenant_id", "id"))]
+    struct Document {
+        tenant_id: i64,
+        id: i32,
+        title: String,
+        tags: Vec<String>,
+        body: Option<String>,
+        #[table(pg_type = "document_state")]
+        state: String,
+        #[table(auto_now_add)]
+        created_at: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+    }
+
+    fn col(name: &str, pg_type: &str, nullable: bool) -> ColumnSchema {
+        ColumnSchema {
+            name: name.into(),
+            pg_type: pg_type.into(),
+            nullable,
+        }
+    }
+
+    #[test]
+    fn test_create_table() {
+        assert_eq!(
+            Schema::new().register::<Document>().create_sql(),
+            r#"CREATE TABLE "app"."documents" (
+    "tenant_id" INT8 NOT NULL,
+    "id" INT4 NOT NULL,
+    "title" TEXT NOT NULL,
+    "tags" TEXT[] NOT NULL,
+    "body" TEXT,
+    "state" document_state NOT NULL,
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY ("tenant_id", "id")
+);"#
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let schema = Schema::new().register::<Document>();
+        assert_eq!(schema.diff_sql(&Snapshot::default()), schema.create_sql());
+
+        let existing = Snapshot {
+            tables: BTreeMap::from([(
+                "app.documents".to_owned(),
+                vec![
+                    col("tenant_id", "int8", false),
+                    col("id", "int8", false),
+                    col("title", "varchar", true),
+                    col("tags", "_varchar", false),
+                    col("body", "text", false),
+                    col("state", "document_state", false),
+                    col("legacy", "text", true),
+                ],
+            )]),
+        };
+        assert_eq!(
+            schema.diff_sql(&existing),
+            r#"ALTER TABLE "app"."documents" ALTER COLUMN "id" TYPE INT4 USING "id"::INT4;
+ALTER TABLE "app"."documents" ALTER COLUMN "title" SET NOT NULL;
+ALTER TABLE "app"."documents" ALTER COLUMN "body" DROP NOT NULL;
+ALTER TABLE "app"."documents" ADD COLUMN "created_at" TIMESTAMPTZ NOT NULL DEFAULT now();
+-- ALTER TABLE "app"."documents" DROP COLUMN "legacy";"#
+        );
+    }
+}
@@ -1,5 +1,29 @@
+pub mod audit;
+#[cfg(feature = "cache")]
+pub mod cache;
+mod composite;
+pub mod copy;
 mod crud;
+#[cfg(feature = "encryption")]
+pub mod encrypted;
+mod error;
+mod jsonb;
 pub mod macros;
+#[cfg(feature = "migrate")]
+pub mod migrate;
+mod pagination;
+mod pg_enum;
+pub mod query;
+pub mod relations;
+mod repository;
+pub mod schema;
+pub mod tx;
 
+pub use composite::*;
 pub use crud::*;
-pub use macros::*;
+pub use error::*;
+pub use jsonb::*;
+pub use pagination::*;
+pub use pg_enum::*;
+pub use query::Queryable;
+pub use repository::*;
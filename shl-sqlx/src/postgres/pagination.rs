@@ -0,0 +1,121 @@
+use super::query::Select;
+use super::{TableMeta, TenantScoped};
+use sqlx::postgres::PgRow;
+use sqlx::{Acquire, Error, Executor, FromRow, Postgres};
+use std::fmt;
+use std::str::FromStr;
+
+/// Name of the extra column carrying the cursor of each row in keyset queries.
+pub(crate) const CURSOR_COL: &str = "__cursor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub(crate) fn sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => " ASC",
+            SortOrder::Desc => " DESC",
+        }
+    }
+
+    pub(crate) fn after_op(self) -> &'static str {
+        match self {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        }
+    }
+}
+
+/// Opaque position of a row in keyset pagination.
+///
+/// Encodes the primary key of the last row of a page; pass it back unchanged to get the next one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cursor(pub(crate) String);
+
+impl Cursor {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("invalid pagination cursor")]
+pub struct InvalidCursor;
+
+impl FromStr for Cursor {
+    type Err = InvalidCursor;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(InvalidCursor);
+        }
+        Ok(Self(s.to_owned()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Set by keyset pagination when `has_more` is true.
+    pub next_cursor: Option<Cursor>,
+    pub has_more: bool,
+    /// Set by offset pagination when the total was requested.
+    pub total: Option<i64>,
+}
+
+/// Listing helpers implemented by `#[derive(Table)]`, see [`Select::fetch_page_after`] and
+/// [`Select::fetch_page`] to combine them with filters.
+pub trait Paginated: TableMeta + for<'r> FromRow<'r, PgRow> + Send + Unpin {
+    /// Keyset pagination on the primary key; works well with time-ordered ids like `uuidv7`.
+    fn page_after<'e, E>(exec: E, after: Option<&Cursor>, limit: i64, order: SortOrder) -> impl Future<Output = Result<Page<Self>, Error>> + Send
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Select::new().fetch_page_after(exec, after, limit, order)
+    }
+
+    /// Classic `LIMIT`/`OFFSET` pagination ordered by the primary key.
+    fn page<'a, A>(conn: A, offset: i64, limit: i64, with_total: bool) -> impl Future<Output = Result<Page<Self>, Error>> + Send
+    where
+        A: Acquire<'a, Database = Postgres> + Send,
+    {
+        Select::new().order_by_pk(SortOrder::Asc).fetch_page(conn, offset, limit, with_total)
+    }
+}
+
+/// [`Paginated`] scoped to a tenant.
+pub trait TenantPaginated: TenantScoped + for<'r> FromRow<'r, PgRow> + Send + Unpin {
+    fn page_after<'e, E>(
+        exec: E,
+        tenant: Self::Tenant,
+        after: Option<&Cursor>,
+        limit: i64,
+        order: SortOrder,
+    ) -> impl Future<Output = Result<Page<Self>, Error>> + Send
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        Select::new().tenant(tenant).fetch_page_after(exec, after, limit, order)
+    }
+
+    fn page<'a, A>(conn: A, tenant: Self::Tenant, offset: i64, limit: i64, with_total: bool) -> impl Future<Output = Result<Page<Self>, Error>> + Send
+    where
+        A: Acquire<'a, Database = Postgres> + Send,
+    {
+        Select::new()
+            .tenant(tenant)
+            .order_by_pk(SortOrder::Asc)
+            .fetch_page(conn, offset, limit, with_total)
+    }
+}
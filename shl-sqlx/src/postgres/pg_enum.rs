@@ -0,0 +1,27 @@
+/// Implemented by `#[derive(PgEnum)]` for unit enums, together with `Display`, `FromStr` and the
+/// sqlx `Type`/`Encode`/`Decode`/`PgHasArrayType` traits.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+/// #[pg_enum(type_name = "integration_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+/// pub enum IntegrationKind {
+///     Google,
+///     #[pg_enum(rename = "MS")]
+///     Microsoft,
+/// }
+/// ```
+///
+/// Without `type_name` the values are stored as `varchar`, like `impl_to_string_varchar!`.
+/// `rename_all` takes the serde rules (`snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`, ...);
+/// by default the variant name is used as is.
+pub trait PgEnum: Sized + 'static {
+    /// The native enum type, or `varchar`.
+    const TYPE_NAME: &'static str;
+    const ALL_VARIANTS: &'static [Self];
+    /// Database labels in `ALL_VARIANTS` order.
+    const LABELS: &'static [&'static str];
+    /// `CREATE TYPE ... AS ENUM (...)` for native enums.
+    const SQL_CREATE_TYPE: Option<&'static str>;
+
+    fn as_str(&self) -> &'static str;
+}
@@ -0,0 +1,586 @@
+use super::pagination::CURSOR_COL;
+use super::{Cursor, Page, SortOrder, TableMeta, TenantScoped};
+use sqlx::postgres::PgRow;
+use sqlx::{Acquire, Encode, Error, Executor, FromRow, Postgres, QueryBuilder, Row, Type};
+use std::marker::PhantomData;
+
+/// Entry point of the typed query builder, implemented by `#[derive(Table)]`.
+///
+/// ```ignore
+/// let users = User::select()
+///     .filter(User::cols().email.eq("alice@example.com".to_owned()))
+///     .order_by(User::cols().created_at.desc())
+///     .limit(10)
+///     .fetch_all(&pool)
+///     .await?;
+/// ```
+pub trait Queryable: TableMeta {
+    type Columns;
+
+    fn cols() -> Self::Columns;
+
+    fn select() -> Select<Self> {
+        Select::new()
+    }
+}
+
+/// Any value that can be bound as a query parameter.
+///
+/// Values are cloned on binding so the same filters can be rendered more than once
+/// (e.g. for a page and its total count).
+pub trait Value: for<'q> Encode<'q, Postgres> + Type<Postgres> + Clone + Send + Sync + 'static {}
+
+impl<V> Value for V where V: for<'q> Encode<'q, Postgres> + Type<Postgres> + Clone + Send + Sync + 'static {}
+
+/// Columns that support `LIKE` / `ILIKE`.
+pub trait Text {}
+
+impl Text for String {}
+impl Text for Option<String> {}
+
+trait Bind: Send + Sync {
+    fn push(&self, qb: &mut QueryBuilder<'_, Postgres>);
+}
+
+impl<V: Value> Bind for V {
+    fn push(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        qb.push_bind(self.clone());
+    }
+}
+
+/// A column of table `T` holding values of type `V`.
+pub struct Column<T, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (T, V)>,
+}
+
+impl<T, V> Clone for Column<T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V> Copy for Column<T, V> {}
+
+impl<T, V> Column<T, V> {
+    pub const fn new(name: &'static str) -> Self {
+        Self { name, _marker: PhantomData }
+    }
+
+    /// Quoted SQL column name.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn asc(self) -> OrderBy<T> {
+        OrderBy::new(self.name, false)
+    }
+
+    pub fn desc(self) -> OrderBy<T> {
+        OrderBy::new(self.name, true)
+    }
+
+    pub fn is_null(self) -> Condition<T> {
+        Condition::new(Node::Null {
+            col: self.name,
+            negated: false,
+        })
+    }
+
+    pub fn is_not_null(self) -> Condition<T> {
+        Condition::new(Node::Null {
+            col: self.name,
+            negated: true,
+        })
+    }
+}
+
+impl<T, V: Value> Column<T, V> {
+    fn compare(self, op: &'static str, value: V) -> Condition<T> {
+        Condition::new(Node::Compare {
+            col: self.name,
+            op,
+            value: Box::new(value),
+        })
+    }
+
+    pub fn eq(self, value: impl Into<V>) -> Condition<T> {
+        self.compare("=", value.into())
+    }
+
+    pub fn ne(self, value: impl Into<V>) -> Condition<T> {
+        self.compare("<>", value.into())
+    }
+
+    pub fn lt(self, value: impl Into<V>) -> Condition<T> {
+        self.compare("<", value.into())
+    }
+
+    pub fn le(self, value: impl Into<V>) -> Condition<T> {
+        self.compare("<=", value.into())
+    }
+
+    pub fn gt(self, value: impl Into<V>) -> Condition<T> {
+        self.compare(">", value.into())
+    }
+
+    pub fn ge(self, value: impl Into<V>) -> Condition<T> {
+        self.compare(">=", value.into())
+    }
+
+    /// `"col" = ANY($n)`, binding all values as a single array parameter.
+    pub fn eq_any(self, values: impl IntoIterator<Item = impl Into<V>>) -> Condition<T>
+    where
+        Vec<V>: Value,
+    {
+        let values: Vec<V> = values.into_iter().map(Into::into).collect();
+        Condition::new(Node::Any {
+            col: self.name,
+            value: Box::new(values),
+        })
+    }
+}
+
+impl<T, V: Text> Column<T, V> {
+    pub fn like(self, pattern: impl Into<String>) -> Condition<T> {
+        Condition::new(Node::Compare {
+            col: self.name,
+            op: "LIKE",
+            value: Box::new(pattern.into()),
+        })
+    }
+
+    pub fn ilike(self, pattern: impl Into<String>) -> Condition<T> {
+        Condition::new(Node::Compare {
+            col: self.name,
+            op: "ILIKE",
+            value: Box::new(pattern.into()),
+        })
+    }
+}
+
+enum Node {
+    Compare {
+        col: &'static str,
+        op: &'static str,
+        value: Box<dyn Bind>,
+    },
+    Any {
+        col: &'static str,
+        value: Box<dyn Bind>,
+    },
+    Null {
+        col: &'static str,
+        negated: bool,
+    },
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+}
+
+impl Node {
+    fn push(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        match self {
+            Node::Compare { col, op, value } => {
+                qb.push(col).push(" ").push(op).push(" ");
+                value.push(qb);
+            }
+            Node::Any { col, value } => {
+                qb.push(col).push(" = ANY(");
+                value.push(qb);
+                qb.push(")");
+            }
+            Node::Null { col, negated } => {
+                qb.push(col).push(if *negated { " IS NOT NULL" } else { " IS NULL" });
+            }
+            Node::And(nodes) => Self::push_joined(nodes, " AND ", qb),
+            Node::Or(nodes) => Self::push_joined(nodes, " OR ", qb),
+            Node::Not(node) => {
+                qb.push("NOT ");
+                node.push(qb);
+            }
+        }
+    }
+
+    fn push_joined(nodes: &[Node], sep: &str, qb: &mut QueryBuilder<'_, Postgres>) {
+        qb.push("(");
+        for (i, node) in nodes.iter().enumerate() {
+            if i > 0 {
+                qb.push(sep);
+            }
+            node.push(qb);
+        }
+        qb.push(")");
+    }
+}
+
+/// A boolean SQL expression over the columns of table `T`.
+pub struct Condition<T> {
+    node: Node,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Condition<T> {
+    fn new(node: Node) -> Self {
+        Self { node, _marker: PhantomData }
+    }
+
+    pub fn and(self, other: Condition<T>) -> Self {
+        match self.node {
+            Node::And(mut nodes) => {
+                nodes.push(other.node);
+                Self::new(Node::And(nodes))
+            }
+            node => Self::new(Node::And(vec![node, other.node])),
+        }
+    }
+
+    pub fn or(self, other: Condition<T>) -> Self {
+        match self.node {
+            Node::Or(mut nodes) => {
+                nodes.push(other.node);
+                Self::new(Node::Or(nodes))
+            }
+            node => Self::new(Node::Or(vec![node, other.node])),
+        }
+    }
+}
+
+impl<T> std::ops::Not for Condition<T> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::new(Node::Not(Box::new(self.node)))
+    }
+}
+
+pub struct OrderBy<T> {
+    col: &'static str,
+    desc: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> OrderBy<T> {
+    fn new(col: &'static str, desc: bool) -> Self {
+        Self {
+            col,
+            desc,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// `SELECT` over `T::COLS`, built by [`Queryable::select`].
+pub struct Select<T> {
+    filters: Vec<Node>,
+    with_deleted: bool,
+    tenant: Option<Node>,
+    order: Vec<OrderBy<T>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+impl<T: TableMeta> Default for Select<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TableMeta> Select<T> {
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            with_deleted: false,
+            tenant: None,
+            order: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Adds a condition; multiple filters are combined with `AND`.
+    pub fn filter(mut self, cond: Condition<T>) -> Self {
+        self.filters.push(cond.node);
+        self
+    }
+
+    /// Includes soft-deleted rows, which are skipped by default for `#[table(soft_delete = "...")]` types.
+    pub fn with_deleted(mut self) -> Self {
+        self.with_deleted = true;
+        self
+    }
+
+    /// Restricts a `#[table(tenant = "...")]` type to the rows of `tenant`; without it such a
+    /// select matches nothing.
+    pub fn tenant(mut self, tenant: T::Tenant) -> Self
+    where
+        T: TenantScoped,
+    {
+        self.tenant = T::TENANT_COL.map(|col| Node::Compare {
+            col,
+            op: "=",
+            value: Box::new(tenant),
+        });
+        self
+    }
+
+    pub fn order_by(mut self, order: OrderBy<T>) -> Self {
+        self.order.push(order);
+        self
+    }
+
+    pub fn order_by_pk(mut self, order: SortOrder) -> Self {
+        for col in T::PK_COLS {
+            self.order.push(OrderBy::new(col, order == SortOrder::Desc));
+        }
+        self
+    }
+
+    pub fn limit(mut self, n: i64) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    pub fn offset(mut self, n: i64) -> Self {
+        self.offset = Some(n);
+        self
+    }
+
+    /// Returns whether a `WHERE` clause was written.
+    fn push_where(&self, qb: &mut QueryBuilder<'_, Postgres>) -> bool {
+        let mut has_where = false;
+        if T::TENANT_COL.is_some() {
+            qb.push(" WHERE ");
+            match &self.tenant {
+                Some(node) => node.push(qb),
+                None => {
+                    qb.push("false");
+                }
+            }
+            has_where = true;
+        }
+        if let Some(col) = T::SOFT_DELETE_COL.filter(|_| !self.with_deleted) {
+            qb.push(if has_where { " AND " } else { " WHERE " }).push(col).push(" IS NULL");
+            has_where = true;
+        }
+        for node in &self.filters {
+            qb.push(if has_where { " AND " } else { " WHERE " });
+            node.push(qb);
+            has_where = true;
+        }
+        has_where
+    }
+
+    fn push_order(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        for (i, o) in self.order.iter().enumerate() {
+            qb.push(if i == 0 { " ORDER BY " } else { ", " });
+            qb.push(o.col).push(if o.desc { " DESC" } else { " ASC" });
+        }
+    }
+
+    /// Renders the query, leaving the builder open for further clauses.
+    pub fn into_query_builder(self) -> QueryBuilder<'static, Postgres> {
+        let mut qb = QueryBuilder::new(format!("SELECT {} FROM {}", T::COLS.join(", "), T::QUAL_TABLE));
+        self.push_where(&mut qb);
+        self.push_order(&mut qb);
+        if let Some(limit) = self.limit {
+            qb.push(" LIMIT ").push_bind(limit);
+        }
+        if let Some(offset) = self.offset {
+            qb.push(" OFFSET ").push_bind(offset);
+        }
+        qb
+    }
+
+    pub async fn fetch_all<'e, E>(self, exec: E) -> Result<Vec<T>, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: Executor<'e, Database = Postgres>,
+    {
+        self.into_query_builder().build_query_as::<T>().fetch_all(exec).await
+    }
+
+    pub async fn fetch_one<'e, E>(self, exec: E) -> Result<T, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: Executor<'e, Database = Postgres>,
+    {
+        self.into_query_builder().build_query_as::<T>().fetch_one(exec).await
+    }
+
+    pub async fn fetch_optional<'e, E>(self, exec: E) -> Result<Option<T>, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: Executor<'e, Database = Postgres>,
+    {
+        self.into_query_builder().build_query_as::<T>().fetch_optional(exec).await
+    }
+
+    /// `SELECT COUNT(*)` honoring the filters; ordering and limits are ignored.
+    pub async fn count<'e, E>(&self, exec: E) -> Result<i64, Error>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let mut qb = QueryBuilder::new(format!("SELECT COUNT(*) FROM {}", T::QUAL_TABLE));
+        self.push_where(&mut qb);
+        let (count,): (i64,) = qb.build_query_as().fetch_one(exec).await?;
+        Ok(count)
+    }
+
+    fn keyset_query_builder(&self, after: Option<&Cursor>, limit: i64, order: SortOrder) -> QueryBuilder<'static, Postgres> {
+        let pk = T::PK_COLS.join(", ");
+        let cursor_obj = T::PK_COLS
+            .iter()
+            .map(|c| format!("'{}', {}", c.trim_matches('"').replace('\'', "''"), c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut qb = QueryBuilder::new(format!(
+            "SELECT {}, encode(convert_to(jsonb_build_object({})::text, 'UTF8'), 'hex') AS \"{}\" FROM {}",
+            T::COLS.join(", "),
+            cursor_obj,
+            CURSOR_COL,
+            T::QUAL_TABLE
+        ));
+        let has_where = self.push_where(&mut qb);
+        if let Some(after) = after {
+            qb.push(if has_where { " AND " } else { " WHERE " });
+            qb.push(format!(
+                "({}) {} (SELECT {} FROM jsonb_populate_record(NULL::{}, ",
+                pk,
+                order.after_op(),
+                pk,
+                T::QUAL_TABLE
+            ));
+            qb.push("convert_from(decode(")
+                .push_bind(after.as_str().to_owned())
+                .push(", 'hex'), 'UTF8')::jsonb))");
+        }
+        for (i, c) in T::PK_COLS.iter().enumerate() {
+            qb.push(if i == 0 { " ORDER BY " } else { ", " });
+            qb.push(c).push(order.sql());
+        }
+        // one extra row tells whether there is a next page
+        qb.push(" LIMIT ").push_bind(limit.saturating_add(1));
+        qb
+    }
+
+    /// Keyset pagination on `T::PK_COLS`, ignoring any `order_by`, `limit` and `offset` set on the builder.
+    pub async fn fetch_page_after<'e, E>(self, exec: E, after: Option<&Cursor>, limit: i64, order: SortOrder) -> Result<Page<T>, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        E: Executor<'e, Database = Postgres>,
+    {
+        check_limit(limit)?;
+        let mut qb = self.keyset_query_builder(after, limit, order);
+        let mut rows = qb.build().fetch_all(exec).await?;
+        let has_more = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+
+        let next_cursor = match rows.last() {
+            Some(row) if has_more => Some(Cursor(row.try_get(CURSOR_COL)?)),
+            _ => None,
+        };
+        let items = rows.iter().map(T::from_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page {
+            items,
+            next_cursor,
+            has_more,
+            total: None,
+        })
+    }
+
+    /// `LIMIT`/`OFFSET` pagination honoring `order_by`; the total is counted with a second
+    /// query on the same connection when requested.
+    pub async fn fetch_page<'a, A>(self, conn: A, offset: i64, limit: i64, with_total: bool) -> Result<Page<T>, Error>
+    where
+        T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+        A: Acquire<'a, Database = Postgres>,
+    {
+        check_limit(limit)?;
+        let mut conn = conn.acquire().await?;
+        let total = if with_total { Some(self.count(&mut *conn).await?) } else { None };
+
+        let mut qb = QueryBuilder::new(format!("SELECT {} FROM {}", T::COLS.join(", "), T::QUAL_TABLE));
+        self.push_where(&mut qb);
+        self.push_order(&mut qb);
+        qb.push(" LIMIT ").push_bind(limit.saturating_add(1));
+        qb.push(" OFFSET ").push_bind(offset);
+        let mut items = qb.build_query_as::<T>().fetch_all(&mut *conn).await?;
+        let has_more = items.len() as i64 > limit;
+        items.truncate(limit as usize);
+
+        Ok(Page {
+            items,
+            next_cursor: None,
+            has_more,
+            total,
+        })
+    }
+}
+
+/// Postgres rejects a negative `LIMIT`, but `limit + 1` would hide it.
+fn check_limit(limit: i64) -> Result<(), Error> {
+    if limit < 0 {
+        return Err(Error::InvalidArgument(format!("negative page limit {}", limit)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as shl_sqlx;
+    use shl_sqlx::Table;
+
+    #[derive(sqlx::FromRow, Table)]
+    #[allow(dead_code)]
+    struct Account {
+        id: i64,
+        #[table(rename = "mail")]
+        email: Option<String>,
+        score: i32,
+    }
+
+    #[test]
+    fn test_select_all() {
+        let qb = Account::select().into_query_builder();
+        assert_eq!(qb.sql(), r#"SELECT "id", "mail", "score" FROM "public"."accounts""#);
+    }
+
+    #[test]
+    fn test_select_filtered() {
+        let c = Account::cols();
+        let qb = Account::select()
+            .filter(c.email.eq("a@b.c".to_owned()).or(c.email.is_null()))
+            .filter(!c.id.eq_any([1, 2, 3]))
+            .filter(c.score.ge(10).and(c.score.lt(20)).and(c.email.ilike("%@b.c")))
+            .order_by(c.score.desc())
+            .order_by(c.id.asc())
+            .limit(5)
+            .offset(10)
+            .into_query_builder();
+        assert_eq!(
+            qb.sql(),
+            r#"SELECT "id", "mail", "score" FROM "public"."accounts" WHERE ("mail" = $1 OR "mail" IS NULL) AND NOT "id" = ANY($2) AND ("score" >= $3 AND "score" < $4 AND "mail" ILIKE $5) ORDER BY "score" DESC, "id" ASC LIMIT $6 OFFSET $7"#
+        );
+    }
+
+    #[test]
+    fn test_keyset_after_cursor() {
+        let cursor: Cursor = "7b226964223a20317d".parse().unwrap();
+        let qb = Account::select()
+            .filter(Account::cols().score.gt(0))
+            .keyset_query_builder(Some(&cursor), 20, SortOrder::Desc);
+        assert_eq!(
+            qb.sql(),
+            r#"SELECT "id", "mail", "score", encode(convert_to(jsonb_build_object('id', "id")::text, 'UTF8'), 'hex') AS "__cursor" FROM "public"."accounts" WHERE "score" > $1 AND ("id") < (SELECT "id" FROM jsonb_populate_record(NULL::"public"."accounts", convert_from(decode($2, 'hex'), 'UTF8')::jsonb)) ORDER BY "id" DESC LIMIT $3"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        assert!("".parse::<Cursor>().is_err());
+        assert!("abc".parse::<Cursor>().is_err());
+        assert!("zz".parse::<Cursor>().is_err());
+    }
+}
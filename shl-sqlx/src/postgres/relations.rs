@@ -0,0 +1,40 @@
+//! Batch loading behind `#[table(has_many(...))]`.
+use super::query::{Column, Queryable, Value};
+use sqlx::postgres::PgRow;
+use sqlx::{Error, Executor, FromRow, Postgres};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Loads the children of all `parent_ids` with a single `"fk" = ANY($1)` query and groups them
+/// in the order of `parent_ids`. Tenant-scoped children are rejected at compile time, as they
+/// can't be selected without a tenant.
+pub async fn load_children<'e, E, C, V, K>(
+    exec: E,
+    fk: Column<C, V>,
+    parent_ids: impl IntoIterator<Item = K>,
+    fk_value: impl Fn(&C) -> &V,
+) -> Result<Vec<Vec<C>>, Error>
+where
+    E: Executor<'e, Database = Postgres>,
+    C: Queryable + for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    V: Value + Eq + Hash,
+    Vec<V>: Value,
+    K: Into<V>,
+{
+    const { assert!(C::TENANT_COL.is_none(), "load_children can't load tenant-scoped rows") };
+    let keys: Vec<V> = parent_ids.into_iter().map(Into::into).collect();
+    let rows = C::select().filter(fk.eq_any(keys.clone())).fetch_all(exec).await?;
+
+    // first occurrence wins when the same parent is passed twice
+    let mut index = HashMap::with_capacity(keys.len());
+    for (i, key) in keys.iter().enumerate() {
+        index.entry(key).or_insert(i);
+    }
+    let mut out: Vec<Vec<C>> = keys.iter().map(|_| Vec::new()).collect();
+    for row in rows {
+        if let Some(&i) = index.get(fk_value(&row)) {
+            out[i].push(row);
+        }
+    }
+    Ok(out)
+}
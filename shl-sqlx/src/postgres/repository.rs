@@ -0,0 +1,297 @@
+use super::{BulkInsertable, CopyUpdated, DbError, HasId, Insertable, Readable, Updatable};
+use sqlx::{Error, PgPool};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+/// The CRUD operations of a table behind a value services can hold instead of a `PgPool`, so
+/// they can be tested against [`InMemoryRepository`].
+///
+/// ```ignore
+/// pub struct UserService<R> {
+///     users: R,
+/// }
+///
+/// impl<R: Repository<User>> UserService<R> {
+///     pub async fn rename(&self, id: Uuid, name: String) -> Result<User, DbError> {
+///         let mut user = self.users.find_by_id(id).await?;
+///         user.name = name;
+///         Ok(self.users.update_returning(&user).await?)
+///     }
+/// }
+///
+/// let service = UserService { users: PgRepository::new(pool) };
+/// let service = UserService { users: InMemoryRepository::new() };
+/// ```
+pub trait Repository<T>: Send + Sync
+where
+    T: Readable + Insertable + Updatable,
+{
+    fn find_by_id(&self, id: T::Id) -> impl Future<Output = Result<T, DbError>> + Send;
+
+    fn find_optional_by_id(&self, id: T::Id) -> impl Future<Output = Result<Option<T>, DbError>> + Send;
+
+    /// Rows are returned in no particular order; missing ids are skipped.
+    fn find_by_ids(&self, ids: &[T::Id]) -> impl Future<Output = Result<Vec<T>, DbError>> + Send;
+
+    fn exists_by_id(&self, id: T::Id) -> impl Future<Output = Result<bool, DbError>> + Send;
+
+    fn count(&self) -> impl Future<Output = Result<i64, DbError>> + Send;
+
+    fn insert(&self, row: &T) -> impl Future<Output = Result<u64, DbError>> + Send;
+
+    fn insert_returning(&self, row: &T) -> impl Future<Output = Result<T, DbError>> + Send;
+
+    /// Only available for `#[table(bulk)]` types, see [`BulkInsertable`].
+    fn insert_many(&self, rows: &[T]) -> impl Future<Output = Result<u64, DbError>> + Send
+    where
+        T: BulkInsertable;
+
+    fn update(&self, row: &T) -> impl Future<Output = Result<u64, T::Error>> + Send;
+
+    fn update_returning(&self, row: &T) -> impl Future<Output = Result<T, T::Error>> + Send;
+
+    fn delete_by_id(&self, id: T::Id) -> impl Future<Output = Result<u64, DbError>> + Send;
+}
+
+/// [`Repository`] running the generated statements on a pool.
+pub struct PgRepository<T> {
+    pool: PgPool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PgRepository<T> {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, _marker: PhantomData }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+impl<T> Clone for PgRepository<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.pool.clone())
+    }
+}
+
+impl<T> Repository<T> for PgRepository<T>
+where
+    T: Readable + Insertable + Updatable + Send + Sync,
+    T::Id: Send + Sync,
+{
+    async fn find_by_id(&self, id: T::Id) -> Result<T, DbError> {
+        Ok(T::find_by_id(&self.pool, id).await?)
+    }
+
+    async fn find_optional_by_id(&self, id: T::Id) -> Result<Option<T>, DbError> {
+        Ok(T::find_optional_by_id(&self.pool, id).await?)
+    }
+
+    async fn find_by_ids(&self, ids: &[T::Id]) -> Result<Vec<T>, DbError> {
+        Ok(T::find_by_ids(&self.pool, ids).await?)
+    }
+
+    async fn exists_by_id(&self, id: T::Id) -> Result<bool, DbError> {
+        Ok(T::exists_by_id(&self.pool, id).await?)
+    }
+
+    async fn count(&self) -> Result<i64, DbError> {
+        Ok(T::count(&self.pool).await?)
+    }
+
+    async fn insert(&self, row: &T) -> Result<u64, DbError> {
+        Ok(row.insert(&self.pool).await?)
+    }
+
+    async fn insert_returning(&self, row: &T) -> Result<T, DbError> {
+        Ok(row.insert_returning(&self.pool).await?)
+    }
+
+    async fn insert_many(&self, rows: &[T]) -> Result<u64, DbError>
+    where
+        T: BulkInsertable,
+    {
+        Ok(T::insert_many(&self.pool, rows).await?)
+    }
+
+    async fn update(&self, row: &T) -> Result<u64, T::Error> {
+        row.update(&self.pool).await
+    }
+
+    async fn update_returning(&self, row: &T) -> Result<T, T::Error> {
+        row.update_returning(&self.pool).await
+    }
+
+    async fn delete_by_id(&self, id: T::Id) -> Result<u64, DbError> {
+        Ok(T::delete_by_id(&self.pool, id).await?)
+    }
+}
+
+/// [`Repository`] keeping rows in a map keyed by [`HasId::id`], for tests.
+///
+/// Rows are inserted exactly as passed: database defaults, `auto_now` columns and constraints other
+/// than the primary key are not emulated. Inserting an existing id fails with
+/// [`DbError::UniqueViolation`] on `<table>_pkey`. Updates copy the fields the generated `UPDATE`
+/// writes (see [`CopyUpdated`]) and keep the others, but leave `auto_now_update` columns as they
+/// were instead of setting them to the current time. Version checks and soft deletes are not emulated
+/// either, so updates of `#[table(version = "...")]` types and deletes of `#[table(soft_delete =
+/// "...")]` types fail with [`Error::InvalidArgument`] instead of behaving unlike the database.
+pub struct InMemoryRepository<T: Readable> {
+    rows: Mutex<HashMap<T::Id, T>>,
+}
+
+impl<T> InMemoryRepository<T>
+where
+    T: Readable + HasId + Clone,
+    T::Id: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            rows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A repository already holding `rows`, later rows replacing earlier ones with the same id.
+    pub fn with_rows(rows: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            rows: Mutex::new(rows.into_iter().map(|row| (row.id(), row)).collect()),
+        }
+    }
+
+    /// All stored rows, in no particular order.
+    pub fn rows(&self) -> Vec<T> {
+        self.lock().values().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<T::Id, T>> {
+        self.rows.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn insert_row(rows: &mut HashMap<T::Id, T>, row: &T) -> Result<(), DbError> {
+        match rows.entry(row.id()) {
+            Entry::Occupied(_) => Err(unique_violation::<T>()),
+            Entry::Vacant(entry) => {
+                entry.insert(row.clone());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<T> Default for InMemoryRepository<T>
+where
+    T: Readable + HasId + Clone,
+    T::Id: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The error of a duplicate primary key, on Postgres' default `<table>_pkey` constraint name.
+fn unique_violation<T: Readable>() -> DbError {
+    let table = T::QUAL_TABLE.rsplit('.').next().unwrap_or_default().replace('"', "");
+    DbError::UniqueViolation {
+        constraint: format!("{}_pkey", table),
+    }
+}
+
+/// Fails when `T` has the column of a feature [`InMemoryRepository`] does not emulate.
+fn unsupported<T: Readable>(col: Option<&str>, feature: &str) -> Result<(), Error> {
+    match col {
+        Some(col) => Err(Error::InvalidArgument(format!(
+            "InMemoryRepository does not emulate the {} column {} of {}",
+            feature,
+            col,
+            T::QUAL_TABLE
+        ))),
+        None => Ok(()),
+    }
+}
+
+impl<T> Repository<T> for InMemoryRepository<T>
+where
+    T: Readable + Insertable + Updatable + CopyUpdated + HasId + Clone + Send + Sync,
+    T::Id: Eq + Hash + Send + Sync,
+{
+    async fn find_by_id(&self, id: T::Id) -> Result<T, DbError> {
+        self.lock().get(&id).cloned().ok_or(DbError::NotFound)
+    }
+
+    async fn find_optional_by_id(&self, id: T::Id) -> Result<Option<T>, DbError> {
+        Ok(self.lock().get(&id).cloned())
+    }
+
+    async fn find_by_ids(&self, ids: &[T::Id]) -> Result<Vec<T>, DbError> {
+        let rows = self.lock();
+        Ok(ids.iter().filter_map(|id| rows.get(id).cloned()).collect())
+    }
+
+    async fn exists_by_id(&self, id: T::Id) -> Result<bool, DbError> {
+        Ok(self.lock().contains_key(&id))
+    }
+
+    async fn count(&self) -> Result<i64, DbError> {
+        Ok(self.lock().len() as i64)
+    }
+
+    async fn insert(&self, row: &T) -> Result<u64, DbError> {
+        Self::insert_row(&mut self.lock(), row)?;
+        Ok(1)
+    }
+
+    async fn insert_returning(&self, row: &T) -> Result<T, DbError> {
+        Self::insert_row(&mut self.lock(), row)?;
+        Ok(row.clone())
+    }
+
+    /// All or nothing, like the transaction of [`BulkInsertable::insert_many`].
+    async fn insert_many(&self, rows: &[T]) -> Result<u64, DbError>
+    where
+        T: BulkInsertable,
+    {
+        let mut stored = self.lock();
+        let mut staged = HashMap::new();
+        for row in rows {
+            match staged.entry(row.id()) {
+                Entry::Vacant(entry) if !stored.contains_key(entry.key()) => {
+                    entry.insert(row.clone());
+                }
+                _ => return Err(unique_violation::<T>()),
+            }
+        }
+        stored.extend(staged);
+        Ok(rows.len() as u64)
+    }
+
+    async fn update(&self, row: &T) -> Result<u64, T::Error> {
+        unsupported::<T>(T::VERSION_COL, "version")?;
+        match self.lock().get_mut(&row.id()) {
+            Some(stored) => {
+                row.copy_updated(stored);
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn update_returning(&self, row: &T) -> Result<T, T::Error> {
+        unsupported::<T>(T::VERSION_COL, "version")?;
+        match self.lock().get_mut(&row.id()) {
+            Some(stored) => {
+                row.copy_updated(stored);
+                Ok(stored.clone())
+            }
+            None => Err(Error::RowNotFound.into()),
+        }
+    }
+
+    async fn delete_by_id(&self, id: T::Id) -> Result<u64, DbError> {
+        unsupported::<T>(T::SOFT_DELETE_COL, "soft_delete")?;
+        Ok(self.lock().remove(&id).map_or(0, |_| 1))
+    }
+}
@@ -0,0 +1,113 @@
+//! Offline schema snapshot checked at compile time by `#[table(verify)]`.
+//!
+//! The derive reads `shl-sqlx-schema.json` next to the crate's `Cargo.toml`, or the path in the
+//! `SHL_SQLX_SCHEMA` environment variable. Regenerate it from a migrated local database, e.g. in an
+//! ignored test:
+//!
+//! ```ignore
+//! #[tokio::test]
+//! #[ignore]
+//! async fn write_schema_snapshot() {
+//!     let pool = PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+//!     let snapshot = Snapshot::fetch(&pool, &["public"]).await.unwrap();
+//!     snapshot.write(concat!(env!("CARGO_MANIFEST_DIR"), "/shl-sqlx-schema.json")).unwrap();
+//! }
+//! ```
+use serde_json::{Map, Value, json};
+use sqlx::{Error, Executor, Postgres};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// `information_schema.columns.udt_name`, e.g. `int8`, `timestamptz` or `_text` for `text[]`.
+    pub pg_type: String,
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Columns in ordinal order, keyed by `schema.table`.
+    pub tables: BTreeMap<String, Vec<ColumnSchema>>,
+}
+
+impl Snapshot {
+    /// Reads every table and view of the given schemas from `information_schema`.
+    pub async fn fetch<'e, E>(exec: E, schemas: &[&str]) -> Result<Self, Error>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let rows: Vec<(String, String, String, String, bool)> = sqlx::query_as(
+            "SELECT table_schema::text, table_name::text, column_name::text, udt_name::text, is_nullable = 'YES' \
+             FROM information_schema.columns WHERE table_schema = ANY($1) \
+             ORDER BY table_schema, table_name, ordinal_position",
+        )
+        .bind(schemas)
+        .fetch_all(exec)
+        .await?;
+
+        let mut snapshot = Self::default();
+        for (schema, table, name, pg_type, nullable) in rows {
+            snapshot
+                .tables
+                .entry(format!("{}.{}", schema, table))
+                .or_default()
+                .push(ColumnSchema { name, pg_type, nullable });
+        }
+        Ok(snapshot)
+    }
+
+    pub fn to_json(&self) -> String {
+        let tables: Map<String, Value> = self
+            .tables
+            .iter()
+            .map(|(table, cols)| {
+                let columns: Map<String, Value> = cols
+                    .iter()
+                    .map(|c| (c.name.clone(), json!({ "type": c.pg_type, "nullable": c.nullable })))
+                    .collect();
+                (table.clone(), json!({ "columns": columns }))
+            })
+            .collect();
+        let mut out = serde_json::to_string_pretty(&json!({ "tables": tables })).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let snapshot = Snapshot {
+            tables: BTreeMap::from([(
+                "public.users".to_owned(),
+                vec![
+                    ColumnSchema {
+                        name: "id".into(),
+                        pg_type: "int8".into(),
+                        nullable: false,
+                    },
+                    ColumnSchema {
+                        name: "email".into(),
+                        pg_type: "text".into(),
+                        nullable: true,
+                    },
+                ],
+            )]),
+        };
+        let v: Value = serde_json::from_str(&snapshot.to_json()).unwrap();
+        assert_eq!(v["tables"]["public.users"]["columns"]["id"], json!({ "type": "int8", "nullable": false }));
+        assert_eq!(
+            v["tables"]["public.users"]["columns"]["email"],
+            json!({ "type": "text", "nullable": true })
+        );
+    }
+}
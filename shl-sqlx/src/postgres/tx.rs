@@ -0,0 +1,169 @@
+//! Transactions with retries on serialization failures and deadlocks.
+//!
+//! ```ignore
+//! let user = with_transaction(&pool, IsolationLevel::Serializable, |tx| {
+//!     Box::pin(async move {
+//!         let mut user = User::find_by_id(&mut **tx, id).await?;
+//!         user.balance += 10;
+//!         user.update_returning(&mut **tx).await
+//!     })
+//! })
+//! .await?;
+//! ```
+//!
+//! The closure may run several times, so it must not have side effects outside the transaction.
+use super::{DbError, UpdateError};
+use futures_core::future::BoxFuture;
+use sqlx::{Error, PgPool, Postgres, Transaction};
+use std::time::Duration;
+
+/// SQLSTATEs after which the whole transaction can simply be run again.
+const RETRYABLE: &[&str] = &["40001", "40P01"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsolationLevel {
+    #[default]
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    fn set_sql(self) -> &'static str {
+        match self {
+            Self::ReadCommitted => "SET TRANSACTION ISOLATION LEVEL READ COMMITTED",
+            Self::RepeatableRead => "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+            Self::Serializable => "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+        }
+    }
+}
+
+/// Exponential backoff between attempts, doubling from `initial_backoff` up to `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of runs, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    /// Delay before the attempt following `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Error types a transaction body can return; exposes the underlying database error so
+/// serialization failures can be recognized.
+pub trait TxError: From<Error> {
+    fn sqlx_error(&self) -> Option<&Error>;
+
+    fn is_retryable(&self) -> bool {
+        self.sqlx_error()
+            .and_then(|e| e.as_database_error())
+            .and_then(|e| e.code())
+            .is_some_and(|code| RETRYABLE.contains(&code.as_ref()))
+    }
+}
+
+impl TxError for Error {
+    fn sqlx_error(&self) -> Option<&Error> {
+        Some(self)
+    }
+}
+
+impl TxError for UpdateError {
+    fn sqlx_error(&self) -> Option<&Error> {
+        match self {
+            Self::Sqlx(e) => Some(e),
+            Self::StaleVersion(_) => None,
+        }
+    }
+}
+
+impl TxError for DbError {
+    fn sqlx_error(&self) -> Option<&Error> {
+        match self {
+            Self::Other(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// [`with_transaction_retry`] with the default [`RetryPolicy`].
+pub async fn with_transaction<T, E, F>(pool: &PgPool, isolation: IsolationLevel, f: F) -> Result<T, E>
+where
+    E: TxError,
+    F: for<'c> FnMut(&'c mut Transaction<'static, Postgres>) -> BoxFuture<'c, Result<T, E>>,
+{
+    with_transaction_retry(pool, isolation, RetryPolicy::default(), f).await
+}
+
+/// Runs `f` in a transaction with the given isolation level and commits when it returns `Ok`,
+/// otherwise rolls back. Serialization failures (`40001`) and deadlocks (`40P01`), from `f` or
+/// from the commit, start the transaction over until `policy.max_attempts` is reached.
+pub async fn with_transaction_retry<T, E, F>(pool: &PgPool, isolation: IsolationLevel, policy: RetryPolicy, mut f: F) -> Result<T, E>
+where
+    E: TxError,
+    F: for<'c> FnMut(&'c mut Transaction<'static, Postgres>) -> BoxFuture<'c, Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        let mut tx = pool.begin().await?;
+        sqlx::query(isolation.set_sql()).execute(&mut *tx).await?;
+
+        let res = match f(&mut tx).await {
+            Ok(value) => tx.commit().await.map(|_| value).map_err(E::from),
+            Err(e) => {
+                // the original error is more useful than a failed rollback
+                let _ = tx.rollback().await;
+                Err(e)
+            }
+        };
+
+        match res {
+            Err(e) if attempt < policy.max_attempts && e.is_retryable() => {
+                sqlx_core::rt::sleep(policy.backoff(attempt)).await;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(10));
+        assert_eq!(policy.backoff(2), Duration::from_millis(20));
+        assert_eq!(policy.backoff(3), Duration::from_millis(40));
+        assert_eq!(policy.backoff(4), Duration::from_millis(50));
+        assert_eq!(policy.backoff(100), Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use serde_json::json;
+use shl_sqlx::postgres::audit::{self, Operation};
+use shl_sqlx::postgres::{Insertable, Readable, SoftDeletable, Updatable, Upsertable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, FromRow, Table, Insertable, Updatable)]
+#[table(table = "audit_test_docs", audit = "audit_test_log", version = "version", skip_update("id"))]
+pub struct Doc {
+    pub id: i64,
+    pub body: String,
+    pub version: i32,
+}
+
+#[derive(Debug, FromRow, Table, Insertable, Updatable)]
+#[table(
+    table = "audit_test_notes",
+    audit = "audit_test_note_log",
+    soft_delete = "deleted_at",
+    on_conflict("slug"),
+    skip_update("id", "slug")
+)]
+pub struct Note {
+    pub id: i64,
+    pub slug: String,
+    pub body: String,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+fn note(id: i64, body: &str) -> Note {
+    Note {
+        id,
+        slug: "hello".to_owned(),
+        body: body.to_owned(),
+        deleted_at: None,
+    }
+}
+
+async fn setup() -> PgPool {
+    common::setup(&[
+        "DROP TABLE IF EXISTS audit_test_docs, audit_test_log",
+        "CREATE TABLE audit_test_docs (id BIGINT PRIMARY KEY, body TEXT NOT NULL, version INT NOT NULL)",
+        &audit::create_table_sql(r#""public"."audit_test_log""#),
+    ])
+    .await
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_history() {
+    let pool = setup().await;
+
+    let mut tx = pool.begin().await.unwrap();
+    audit::set_actor(&mut tx, "alice").await.unwrap();
+    let doc = Doc {
+        id: 1,
+        body: "a".to_owned(),
+        version: 0,
+    };
+    assert_eq!(doc.insert(&mut *tx).await.unwrap(), 1);
+    let mut doc = Doc::find_by_id(&mut *tx, 1).await.unwrap();
+    doc.body = "b".to_owned();
+    let doc = doc.update_returning(&mut *tx).await.unwrap();
+    assert_eq!(doc.version, 1);
+    tx.commit().await.unwrap();
+
+    // a stale update writes neither the row nor an audit entry
+    let stale = Doc { version: 0, ..doc };
+    assert!(stale.update(&pool).await.is_err());
+    assert_eq!(Doc::delete_by_id(&pool, 1).await.unwrap(), 1);
+
+    let history = audit::history::<Doc, _>(&pool, json!({ "id": 1 })).await.unwrap();
+    let ops = history.iter().map(|e| e.operation).collect::<Vec<_>>();
+    assert_eq!(ops, [Operation::Insert, Operation::Update, Operation::Delete]);
+    assert_eq!(history[0].table_name, "public.audit_test_docs");
+    assert_eq!(history[0].old_values, None);
+    assert_eq!(history[1].old_values, Some(json!({ "id": 1, "body": "a", "version": 0 })));
+    assert_eq!(history[1].new_values, Some(json!({ "id": 1, "body": "b", "version": 1 })));
+    assert_eq!(history[1].actor_id.as_deref(), Some("alice"));
+    assert_eq!(history[2].new_values, None);
+    assert_eq!(history[2].actor_id, None);
+
+    // rolled back together with the change
+    let mut tx = pool.begin().await.unwrap();
+    let doc = Doc {
+        id: 2,
+        body: "a".to_owned(),
+        version: 0,
+    };
+    doc.insert(&mut *tx).await.unwrap();
+    tx.rollback().await.unwrap();
+    assert!(audit::history::<Doc, _>(&pool, json!({ "id": 2 })).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_other_writes() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS audit_test_notes, audit_test_note_log",
+        "CREATE TABLE audit_test_notes (id BIGINT PRIMARY KEY, slug TEXT NOT NULL UNIQUE, body TEXT NOT NULL, deleted_at TIMESTAMPTZ)",
+        &audit::create_table_sql(r#""public"."audit_test_note_log""#),
+    ])
+    .await;
+
+    // the first upsert inserts, the second updates the row with the same slug
+    assert_eq!(note(1, "a").upsert(&pool).await.unwrap(), 1);
+    assert_eq!(note(1, "b").upsert(&pool).await.unwrap(), 1);
+    assert_eq!(note(2, "c").insert_or_ignore(&pool).await.unwrap(), 0);
+    let patch = NotePatch {
+        body: Some("d".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(Note::update_patch(&pool, 1, (), patch).await.unwrap(), 1);
+    assert_eq!(Note::delete_by_id(&pool, 1).await.unwrap(), 1);
+    assert_eq!(Note::restore_by_id(&pool, 1).await.unwrap(), 1);
+    assert_eq!(Note::restore_by_id(&pool, 1).await.unwrap(), 0);
+    assert_eq!(Note::purge_by_id(&pool, 1).await.unwrap(), 1);
+
+    let history = audit::history::<Note, _>(&pool, json!({ "id": 1 })).await.unwrap();
+    let ops = history.iter().map(|e| e.operation).collect::<Vec<_>>();
+    assert_eq!(
+        ops,
+        [
+            Operation::Insert,
+            Operation::Update,
+            Operation::Update,
+            Operation::Delete,
+            Operation::Update,
+            Operation::Delete
+        ]
+    );
+    let body = |values: &Option<serde_json::Value>| values.as_ref().map(|v| v["body"].clone());
+    assert_eq!(body(&history[0].old_values), None);
+    assert_eq!(body(&history[1].old_values), Some(json!("a")));
+    assert_eq!(body(&history[1].new_values), Some(json!("b")));
+    assert_eq!(body(&history[2].old_values), Some(json!("b")));
+    assert_eq!(body(&history[2].new_values), Some(json!("d")));
+    // the soft delete and the restore set and clear `deleted_at`
+    assert!(history[3].new_values.is_none());
+    assert_ne!(history[4].old_values.as_ref().unwrap()["deleted_at"], json!(null));
+    assert_eq!(history[4].new_values.as_ref().unwrap()["deleted_at"], json!(null));
+    assert_eq!(body(&history[5].old_values), Some(json!("d")));
+    assert!(audit::history::<Note, _>(&pool, json!({ "id": 2 })).await.unwrap().is_empty());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use shl_sqlx::postgres::{BulkInsertable, Insertable, Readable, Updatable, Upsertable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "auto_now_test_posts", bulk)]
+pub struct Post {
+    pub id: i64,
+    pub title: String,
+    #[table(auto_now_add)]
+    pub created_at: DateTime<Utc>,
+    #[table(rename = "modified_at", auto_now_update)]
+    #[sqlx(rename = "modified_at")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The timestamps are left at the epoch, the database must replace them.
+fn post(id: i64, title: &str) -> Post {
+    Post {
+        id,
+        title: title.to_owned(),
+        created_at: DateTime::UNIX_EPOCH,
+        updated_at: DateTime::UNIX_EPOCH,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_timestamps() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS auto_now_test_posts",
+        "CREATE TABLE auto_now_test_posts (id BIGINT PRIMARY KEY, title TEXT NOT NULL, \
+         created_at TIMESTAMPTZ NOT NULL, modified_at TIMESTAMPTZ NOT NULL)",
+    ])
+    .await;
+
+    let inserted = post(1, "a").insert_returning(&pool).await.unwrap();
+    assert!(inserted.created_at > DateTime::UNIX_EPOCH);
+    assert_eq!(inserted.updated_at, inserted.created_at);
+    Post::insert_many(&pool, &[post(2, "b")]).await.unwrap();
+    assert!(Post::find_by_id(&pool, 2).await.unwrap().created_at > DateTime::UNIX_EPOCH);
+
+    // updates only move the update timestamp, whatever the row holds
+    let updated = post(1, "c").update_returning(&pool).await.unwrap();
+    assert_eq!(updated.created_at, inserted.created_at);
+    assert!(updated.updated_at > inserted.updated_at);
+
+    let patch = PostPatch { title: Some("d".to_owned()) };
+    assert_eq!(Post::update_patch(&pool, 1, (), patch).await.unwrap(), 1);
+    let patched = Post::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!((patched.title.as_str(), patched.created_at), ("d", inserted.created_at));
+    assert!(patched.updated_at > updated.updated_at);
+
+    assert_eq!(post(1, "e").upsert(&pool).await.unwrap(), 1);
+    let upserted = Post::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!((upserted.title.as_str(), upserted.created_at), ("e", inserted.created_at));
+    assert!(upserted.updated_at > patched.updated_at);
+}
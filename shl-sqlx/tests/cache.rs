@@ -0,0 +1,233 @@
+mod common;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use shl_redis_cache_service::error::Error;
+use shl_redis_cache_service::{CacheClient, CacheService};
+use shl_sqlx::postgres::cache::{CacheError, CachedReadable, CachedRepository};
+use shl_sqlx::postgres::{Insertable, Readable, Repository};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, Table, Insertable, Updatable)]
+#[table(table = "cache_test_posts")]
+pub struct Post {
+    pub id: i64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, Table, Insertable, Updatable)]
+#[table(table = "cache_test_articles")]
+pub struct Article {
+    pub id: i64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, Table, Insertable, Updatable)]
+#[table(table = "cache_test_notes", soft_delete = "deleted_at")]
+pub struct Note {
+    pub id: i64,
+    pub title: String,
+    #[serde(skip)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+fn note(id: i64, title: &str) -> Note {
+    Note {
+        id,
+        title: title.to_owned(),
+        deleted_at: None,
+    }
+}
+
+#[derive(Default)]
+struct MemoryClient {
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+    /// Makes reads and writes fail, like an unreachable server.
+    down: Arc<AtomicBool>,
+}
+
+impl MemoryClient {
+    fn check(&self) -> Result<(), Error> {
+        match self.down.load(Ordering::Relaxed) {
+            true => Err(serde_json::from_str::<()>("down").unwrap_err().into()),
+            false => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheClient for MemoryClient {
+    async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        self.check()?;
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set_raw(&self, key: &str, _ttl: u64, value: &[u8]) -> Result<(), Error> {
+        self.check()?;
+        self.entries.lock().unwrap().insert(key.to_owned(), value.to_vec());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.check()?;
+        self.entries.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn delete_pattern(&self, pattern: &str) -> Result<(), Error> {
+        let prefix = pattern.trim_end_matches('*');
+        self.entries.lock().unwrap().retain(|k, _| !k.starts_with(prefix));
+        Ok(())
+    }
+
+    async fn delete_keys(&self, keys: impl IntoIterator<Item = impl AsRef<str> + Send> + Send) -> Result<(), Error> {
+        let mut entries = self.entries.lock().unwrap();
+        for key in keys {
+            entries.remove(key.as_ref());
+        }
+        Ok(())
+    }
+}
+
+async fn set_title(pool: &PgPool, id: i64, title: &str) {
+    sqlx::query("UPDATE cache_test_posts SET title = $1 WHERE id = $2")
+        .bind(title)
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_keys() {
+    assert_eq!(Post::cache_key(&7).unwrap(), "shl_sqlx:public.cache_test_posts:7");
+    assert_eq!(Post::cache_pattern(), "shl_sqlx:public.cache_test_posts:*");
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_read_through_and_invalidation() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS cache_test_posts",
+        "CREATE TABLE cache_test_posts (id BIGINT PRIMARY KEY, title TEXT NOT NULL)",
+    ])
+    .await;
+    let cache = CacheService::new(MemoryClient::default(), 60);
+    let post = Post {
+        id: 1,
+        title: "a".to_owned(),
+    };
+    post.insert(&pool).await.unwrap();
+
+    assert_eq!(Post::find_by_id_cached(&pool, &cache, 1).await.unwrap(), post);
+    assert_eq!(cache.get::<Post>(&Post::cache_key(&1).unwrap()).await.unwrap(), Some(post.clone()));
+
+    // served from the cache until invalidated
+    set_title(&pool, 1, "b").await;
+    assert_eq!(Post::find_by_id_cached(&pool, &cache, 1).await.unwrap().title, "a");
+    Post::invalidate(&cache, &1).await.unwrap();
+    assert_eq!(Post::find_by_id_cached(&pool, &cache, 1).await.unwrap().title, "b");
+
+    let post = Post {
+        id: 1,
+        title: "c".to_owned(),
+    };
+    assert_eq!(post.update_cached(&pool, &cache).await.unwrap(), 1);
+    assert_eq!(cache.get::<Post>(&Post::cache_key(&1).unwrap()).await.unwrap(), None);
+    assert_eq!(Post::find_by_id_cached(&pool, &cache, 1).await.unwrap().title, "c");
+
+    let patch = PostPatch { title: Some("d".to_owned()) };
+    assert_eq!(Post::update_patch_cached(&pool, &cache, 1, (), patch).await.unwrap(), 1);
+    assert_eq!(Post::find_by_id_cached(&pool, &cache, 1).await.unwrap().title, "d");
+
+    assert_eq!(Post::delete_by_id_cached(&pool, &cache, 1).await.unwrap(), 1);
+    assert_eq!(cache.get::<Post>(&Post::cache_key(&1).unwrap()).await.unwrap(), None);
+    assert!(Post::find_by_id_cached(&pool, &cache, 1).await.is_err());
+    assert!(Post::find_by_id(&pool, 1).await.is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_cache_failures() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS cache_test_articles",
+        "CREATE TABLE cache_test_articles (id BIGINT PRIMARY KEY, title TEXT NOT NULL)",
+    ])
+    .await;
+    let down = Arc::new(AtomicBool::new(false));
+    let client = MemoryClient {
+        down: down.clone(),
+        ..Default::default()
+    };
+    let cache = CacheService::new(client, 60);
+    let article = Article {
+        id: 1,
+        title: "a".to_owned(),
+    };
+    article.insert(&pool).await.unwrap();
+
+    // an entry of an older row layout is replaced
+    let key = Article::cache_key(&1).unwrap();
+    cache.set(&key, &"old layout").await.unwrap();
+    assert_eq!(Article::find_by_id_cached(&pool, &cache, 1).await.unwrap(), article);
+    assert_eq!(cache.get::<Article>(&key).await.unwrap(), Some(article.clone()));
+
+    // reads go to the database while the cache is down, invalidation fails
+    cache.delete(&key).await.unwrap();
+    down.store(true, Ordering::Relaxed);
+    assert_eq!(Article::find_by_id_cached(&pool, &cache, 1).await.unwrap(), article);
+    assert!(matches!(article.update_cached(&pool, &cache).await, Err(CacheError::Cache(_))));
+    assert!(matches!(Article::find_by_id_cached(&pool, &cache, 2).await, Err(CacheError::Db(_))));
+    let client = MemoryClient {
+        down: down.clone(),
+        ..Default::default()
+    };
+    let repo = CachedRepository::new(pool.clone(), CacheService::new(client, 60));
+    assert_eq!(repo.find_by_id(1).await.unwrap(), article);
+    assert!(matches!(repo.update(&article).await, Err(sqlx::Error::Io(_))));
+    down.store(false, Ordering::Relaxed);
+    assert_eq!(cache.get::<Article>(&key).await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_writes_invalidate() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS cache_test_notes",
+        "CREATE TABLE cache_test_notes (id BIGINT PRIMARY KEY, title TEXT NOT NULL, deleted_at TIMESTAMPTZ)",
+    ])
+    .await;
+    for id in 1..=3 {
+        note(id, "a").insert(&pool).await.unwrap();
+    }
+    let repo = CachedRepository::new(pool.clone(), CacheService::new(MemoryClient::default(), 60));
+    let cache = repo.cache();
+    let cached = async |id| cache.get::<Note>(&Note::cache_key(&id).unwrap()).await.unwrap();
+
+    // every write of the repository drops the row cached by its reads
+    assert_eq!(repo.find_by_id(1).await.unwrap(), note(1, "a"));
+    assert_eq!(cached(1).await, Some(note(1, "a")));
+    assert_eq!(repo.update(&note(1, "b")).await.unwrap(), 1);
+    assert_eq!(Note::find_by_id_cached(&pool, cache, 1).await.unwrap().title, "b");
+    assert_eq!(repo.update_returning(&note(1, "c")).await.unwrap().title, "c");
+    assert_eq!(repo.find_by_id(1).await.unwrap().title, "c");
+    assert_eq!(repo.delete_by_id(1).await.unwrap(), 1);
+    assert_eq!(repo.find_optional_by_id(1).await.unwrap(), None);
+    assert_eq!(cached(1).await, None);
+
+    Note::find_by_id_cached(&pool, cache, 2).await.unwrap();
+    assert_eq!(note(2, "b").upsert_cached(&pool, cache).await.unwrap(), 1);
+    assert_eq!(Note::find_by_id_cached(&pool, cache, 2).await.unwrap().title, "b");
+    assert_eq!(Note::purge_by_id_cached(&pool, cache, 2).await.unwrap(), 1);
+    assert!(Note::find_by_id_cached(&pool, cache, 2).await.is_err());
+
+    Note::find_by_id_cached(&pool, cache, 3).await.unwrap();
+    assert_eq!(Note::delete_returning_cached(&pool, cache, 3).await.unwrap().id, 3);
+    assert_eq!(cached(3).await, None);
+    assert!(Note::find_by_id_cached(&pool, cache, 3).await.is_err());
+}
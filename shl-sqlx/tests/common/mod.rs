@@ -0,0 +1,16 @@
+//! Shared setup of the integration tests that run against the database in `DATABASE_URL`.
+//!
+//! Those tests are `#[ignore]`d so a plain `cargo test` passes without a database; run them with
+//! `DATABASE_URL=... cargo test -- --include-ignored`.
+use sqlx::PgPool;
+
+/// Connects to `DATABASE_URL` and runs `statements` in order, panicking when the variable is not
+/// set or a statement fails.
+pub async fn setup(statements: &[&str]) -> PgPool {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set to run the database tests");
+    let pool = PgPool::connect(&url).await.expect("cannot connect to DATABASE_URL");
+    for sql in statements {
+        sqlx::query(sql).execute(&pool).await.unwrap_or_else(|e| panic!("{sql}: {e}"));
+    }
+    pool
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use shl_sqlx::postgres::PgComposite;
+use shl_sqlx::{PgComposite, impl_to_string_varchar};
+use sqlx::PgPool;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, PgComposite)]
+#[pg_composite(type_name = "composite_test_address")]
+pub struct Address {
+    pub street: String,
+    pub zip: Label,
+    #[pg_composite(rename = "house_no")]
+    pub number: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label(String);
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Label {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Label(s.to_owned()))
+    }
+}
+
+impl_to_string_varchar!(Label);
+
+async fn setup() -> PgPool {
+    common::setup(&[
+        "DROP TABLE IF EXISTS composite_test_places",
+        "DROP TYPE IF EXISTS composite_test_address",
+        &Address::create_type_sql(),
+        "CREATE TABLE composite_test_places (id BIGINT PRIMARY KEY, address composite_test_address NOT NULL, \
+         previous composite_test_address[] NOT NULL, labels VARCHAR[] NOT NULL)",
+    ])
+    .await
+}
+
+#[test]
+fn test_create_type_sql() {
+    assert_eq!(
+        Address::create_type_sql(),
+        r#"CREATE TYPE "composite_test_address" AS ("street" TEXT, "zip" varchar, "house_no" INT4)"#
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_round_trip() {
+    let pool = setup().await;
+
+    let address = Address {
+        street: "Main St".to_owned(),
+        zip: Label("12345".to_owned()),
+        number: Some(7),
+    };
+    let previous = vec![
+        Address {
+            street: "Old Rd".to_owned(),
+            zip: Label("54321".to_owned()),
+            number: None,
+        },
+        address.clone(),
+    ];
+    let labels = vec![Label("home".to_owned()), Label("billing".to_owned())];
+    sqlx::query("INSERT INTO composite_test_places (id, address, previous, labels) VALUES (1, $1, $2, $3)")
+        .bind(&address)
+        .bind(&previous)
+        .bind(&labels)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let row: (Address, Vec<Address>, Vec<Label>) = sqlx::query_as("SELECT address, previous, labels FROM composite_test_places WHERE id = 1")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(row, (address, previous, labels));
+
+    let (zip, number): (String, Option<i32>) = sqlx::query_as("SELECT (address).zip, (previous[1]).house_no FROM composite_test_places WHERE id = 1")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!((zip.as_str(), number), ("12345", None));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use serde::{Deserialize, Serialize};
+use shl_sqlx::postgres::copy::{copy_in, copy_in_statement};
+use shl_sqlx::postgres::query::Queryable;
+use shl_sqlx::postgres::{PgEnum as _, Readable, SortOrder};
+use shl_sqlx::{Insertable, PgEnum, PgJsonb, Table};
+use sqlx::FromRow;
+use sqlx::types::chrono::{DateTime, Utc};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+#[pg_enum(type_name = "copy_test_level", rename_all = "snake_case")]
+pub enum Level {
+    Info,
+    Warn,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PgJsonb)]
+pub struct Fields {
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "copy_test_logs", bulk)]
+pub struct Log {
+    pub id: i64,
+    pub level: Level,
+    pub message: String,
+    pub fields: Fields,
+    pub code: Option<i32>,
+    #[table(auto_now_add)]
+    pub logged_at: DateTime<Utc>,
+}
+
+fn log(id: i64) -> Log {
+    Log {
+        id,
+        level: if id % 2 == 0 { Level::Info } else { Level::Warn },
+        message: format!("message {}", "x".repeat(id as usize % 50)),
+        fields: Fields {
+            user: (id % 3 == 0).then(|| format!("user {}", id)),
+        },
+        code: (id % 4 == 0).then_some(id as i32),
+        logged_at: DateTime::from_timestamp(1_700_000_000 + id, 0).unwrap(),
+    }
+}
+
+/// A stream over an iterator, instead of pulling in `futures` for tests.
+struct Iter<I>(I);
+
+impl<I: Iterator + Unpin> futures_core::Stream for Iter<I> {
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.next())
+    }
+}
+
+#[test]
+fn test_statement() {
+    assert_eq!(
+        copy_in_statement::<Log>(),
+        r#"COPY "public"."copy_test_logs" ("id", "level", "message", "fields", "code", "logged_at") FROM STDIN (FORMAT binary)"#
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_copy_in() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS copy_test_logs",
+        "DROP TYPE IF EXISTS copy_test_level",
+        Level::SQL_CREATE_TYPE.unwrap(),
+        "CREATE TABLE copy_test_logs (id BIGINT PRIMARY KEY, level copy_test_level NOT NULL, message TEXT NOT NULL, \
+         fields JSONB NOT NULL, code INT, logged_at TIMESTAMPTZ NOT NULL)",
+    ])
+    .await;
+
+    // enough rows to be sent in several chunks
+    let logs = (1..=5_000).map(log).collect::<Vec<_>>();
+    assert_eq!(copy_in(&pool, Iter(logs.clone().into_iter())).await.unwrap(), 5_000);
+    let stored = Log::select().order_by_pk(SortOrder::Asc).fetch_all(&pool).await.unwrap();
+    assert_eq!(stored, logs);
+
+    // a failing row rolls back the whole copy
+    let duplicate = [log(5_001), log(1)];
+    assert!(copy_in(&pool, Iter(duplicate.into_iter())).await.is_err());
+    assert!(!Log::exists_by_id(&pool, 5_001).await.unwrap());
+    assert_eq!(copy_in(&pool, Iter(std::iter::empty::<Log>())).await.unwrap(), 0);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use shl_sqlx::postgres::DbError;
+use sqlx::PgPool;
+
+async fn setup() -> PgPool {
+    common::setup(&[
+        "DROP TABLE IF EXISTS db_error_children, db_error_parents",
+        "CREATE TABLE db_error_parents (id BIGINT PRIMARY KEY, name TEXT NOT NULL, age INT CONSTRAINT age_positive CHECK (age > 0))",
+        "CREATE TABLE db_error_children (id BIGINT PRIMARY KEY, parent_id BIGINT NOT NULL REFERENCES db_error_parents (id))",
+        "INSERT INTO db_error_parents (id, name) VALUES (1, 'a')",
+    ])
+    .await
+}
+
+async fn run(pool: &PgPool, sql: &str) -> DbError {
+    sqlx::query(sql).execute(pool).await.map(drop).map_err(DbError::from).unwrap_err()
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_classify() {
+    let pool = setup().await;
+
+    let err = run(&pool, "INSERT INTO db_error_parents (id, name) VALUES (1, 'b')").await;
+    assert!(
+        matches!(&err, DbError::UniqueViolation { constraint } if constraint == "db_error_parents_pkey"),
+        "{err:?}"
+    );
+
+    let err = run(&pool, "INSERT INTO db_error_children (id, parent_id) VALUES (1, 2)").await;
+    assert!(
+        matches!(&err, DbError::ForeignKeyViolation { constraint } if constraint == "db_error_children_parent_id_fkey"),
+        "{err:?}"
+    );
+
+    let err = run(&pool, "INSERT INTO db_error_parents (id) VALUES (2)").await;
+    assert!(matches!(&err, DbError::NotNullViolation { column } if column == "name"), "{err:?}");
+
+    let err = run(&pool, "INSERT INTO db_error_parents (id, name, age) VALUES (2, 'b', 0)").await;
+    assert!(
+        matches!(&err, DbError::CheckViolation { constraint } if constraint == "age_positive"),
+        "{err:?}"
+    );
+
+    let err = run(&pool, "SELECT * FROM db_error_missing").await;
+    assert!(matches!(err, DbError::Other(sqlx::Error::Database(_))), "{err:?}");
+
+    let err: DbError = sqlx::query("SELECT 1 WHERE false").fetch_one(&pool).await.unwrap_err().into();
+    assert!(matches!(err, DbError::NotFound), "{err:?}");
+}
+
+#[cfg(feature = "ntex")]
+#[test]
+fn test_status_codes() {
+    use ntex::http::StatusCode;
+    use ntex::web::WebResponseError;
+
+    let unique = DbError::UniqueViolation {
+        constraint: "users_email_key".to_owned(),
+    };
+    assert_eq!(unique.status_code(), StatusCode::CONFLICT);
+    let not_null = DbError::NotNullViolation { column: "name".to_owned() };
+    assert_eq!(not_null.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(DbError::NotFound.status_code(), StatusCode::NOT_FOUND);
+    let other = DbError::Other(sqlx::Error::PoolTimedOut);
+    assert_eq!(other.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(other.to_string(), "database error");
+}
@@ -0,0 +1,9 @@
+#[test]
+fn derive_ui() {
+    // SAFETY: set before trybuild spawns the compiler, no other thread reads the environment.
+    unsafe { std::env::set_var("SHL_SQLX_SCHEMA", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ui/schema.json")) };
+
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use shl_sqlx::postgres::encrypted::{self, Encrypted, StaticKeyProvider};
+use shl_sqlx::postgres::{BulkInsertable, Insertable, Readable, Updatable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, FromRow, Table, Insertable, Updatable)]
+#[table(table = "encrypted_test_users", bulk)]
+pub struct User {
+    pub id: i64,
+    #[table(encrypted)]
+    pub email: Encrypted<String>,
+    #[table(encrypted)]
+    pub phone: Option<Encrypted<Vec<String>>>,
+}
+
+async fn setup() -> PgPool {
+    common::setup(&[
+        "DROP TABLE IF EXISTS encrypted_test_users",
+        "CREATE TABLE encrypted_test_users (id BIGINT PRIMARY KEY, email TEXT NOT NULL, phone TEXT)",
+    ])
+    .await
+}
+
+async fn stored_email(pool: &PgPool, id: i64) -> String {
+    sqlx::query_scalar("SELECT email FROM encrypted_test_users WHERE id = $1")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_round_trip_and_rotation() {
+    let pool = setup().await;
+    encrypted::set_key_provider(StaticKeyProvider::new("k1").with_key("k1", [7; 32]));
+
+    let user = User {
+        id: 1,
+        email: Encrypted::new("a@example.com".to_owned()),
+        phone: Some(vec!["555".to_owned()].into()),
+    };
+    assert_eq!(user.insert(&pool).await.unwrap(), 1);
+    let stored = stored_email(&pool, 1).await;
+    assert!(stored.starts_with("k1:"));
+    assert!(!stored.contains("example"));
+
+    let user = User::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!(user.email.as_str(), "a@example.com");
+    assert_eq!(user.phone.as_deref().map(|p| p.as_slice()), Some(&["555".to_owned()][..]));
+
+    // rows written with the old key stay readable and are re-encrypted on the next update
+    encrypted::set_key_provider(StaticKeyProvider::new("k2").with_key("k1", [7; 32]).with_key("k2", [8; 32]));
+    let mut user = User::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!(user.email.as_str(), "a@example.com");
+    user.update(&pool).await.unwrap();
+    assert!(stored_email(&pool, 1).await.starts_with("k2:"));
+
+    user.phone = None;
+    let user = user.update_returning(&pool).await.unwrap();
+    assert!(user.phone.is_none());
+
+    let rows = [2, 3].map(|id| User {
+        id,
+        email: Encrypted::new(format!("{}@example.com", id)),
+        phone: None,
+    });
+    assert_eq!(User::insert_many(&pool, &rows).await.unwrap(), 2);
+    assert!(stored_email(&pool, 3).await.starts_with("k2:"));
+    assert_eq!(User::find_by_id(&pool, 3).await.unwrap().email.as_str(), "3@example.com");
+
+    encrypted::set_key_provider(StaticKeyProvider::new("k3").with_key("k3", [9; 32]));
+    assert!(User::find_by_id(&pool, 1).await.is_err());
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use serde::{Deserialize, Serialize};
+use shl_sqlx::postgres::{BulkInsertable, PgEnum as _, Readable};
+use shl_sqlx::{Insertable, PgEnum, PgJsonb, Table};
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+#[pg_enum(type_name = "insert_many_test_kind", rename_all = "snake_case")]
+pub enum Kind {
+    Click,
+    View,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PgJsonb)]
+pub struct Payload {
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "insert_many_test_events", bulk)]
+pub struct Event {
+    pub id: i64,
+    pub kind: Kind,
+    pub previous: Option<Kind>,
+    pub payload: Payload,
+    pub extra: Option<Payload>,
+    pub note: Option<String>,
+}
+
+fn event(id: i64) -> Event {
+    Event {
+        id,
+        kind: if id % 2 == 0 { Kind::Click } else { Kind::View },
+        previous: (id % 3 == 0).then_some(Kind::View),
+        payload: Payload {
+            tags: vec![format!("tag-{}", id)],
+        },
+        extra: (id % 5 == 0).then(|| Payload { tags: vec![] }),
+        note: (id % 7 == 0).then(|| id.to_string()),
+    }
+}
+
+async fn setup() -> PgPool {
+    common::setup(&[
+        "DROP TABLE IF EXISTS insert_many_test_events",
+        "DROP TYPE IF EXISTS insert_many_test_kind",
+        Kind::SQL_CREATE_TYPE.unwrap(),
+        "CREATE TABLE insert_many_test_events (id BIGINT PRIMARY KEY, kind insert_many_test_kind NOT NULL, \
+         previous insert_many_test_kind, payload JSONB NOT NULL, extra JSONB, note TEXT)",
+    ])
+    .await
+}
+
+#[test]
+fn test_sql() {
+    assert_eq!(
+        Event::sql_insert_many(),
+        r#"INSERT INTO "public"."insert_many_test_events" ("id", "kind", "previous", "payload", "extra", "note") SELECT "id", "kind", "previous", "payload", "extra", "note" FROM UNNEST($1::INT8[], $2::insert_many_test_kind[], $3::insert_many_test_kind[], $4::jsonb[], $5::jsonb[], $6::TEXT[]) AS "__rows"("id", "kind", "previous", "payload", "extra", "note")"#
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_chunked_batch() {
+    let pool = setup().await;
+
+    // more than two chunks, the last one partial
+    let rows = (1..=Event::INSERT_MANY_CHUNK as i64 * 2 + 3).map(event).collect::<Vec<_>>();
+    assert_eq!(Event::insert_many(&pool, &rows).await.unwrap(), rows.len() as u64);
+    assert_eq!(Event::count(&pool).await.unwrap(), rows.len() as i64);
+    for id in [1, 15, 35, rows.len() as i64] {
+        assert_eq!(Event::find_by_id(&pool, id).await.unwrap(), event(id));
+    }
+    let nulls: (i64, i64, i64) = sqlx::query_as(
+        "SELECT count(*) FILTER (WHERE previous IS NULL), count(*) FILTER (WHERE extra IS NULL), count(*) FILTER (WHERE note IS NULL) \
+         FROM insert_many_test_events",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let expected = |n: usize| (rows.len() - rows.len() / n) as i64;
+    assert_eq!(nulls, (expected(3), expected(5), expected(7)));
+
+    // a duplicate in the last chunk rolls back the earlier ones
+    let rows = (100_000..100_000 + Event::INSERT_MANY_CHUNK as i64)
+        .chain([1])
+        .map(event)
+        .collect::<Vec<_>>();
+    assert!(Event::insert_many(&pool, &rows).await.is_err());
+    assert!(!Event::exists_by_id(&pool, 100_000).await.unwrap());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use shl_sqlx::postgres::PgEnum as _;
+use shl_sqlx::postgres::migrate::Schema;
+use shl_sqlx::{PgEnum, Table};
+use sqlx::FromRow;
+use sqlx::types::Uuid;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+#[pg_enum(type_name = "migrate_test.state", rename_all = "snake_case")]
+pub enum State {
+    Draft,
+    Published,
+}
+
+#[derive(Debug, FromRow, Table)]
+#[table(schema = "migrate_test", table = "documents", pk("tenant_id", "id"))]
+pub struct Document {
+    pub tenant_id: i64,
+    pub id: Uuid,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub body: Option<String>,
+    pub state: State,
+    #[table(auto_now_add)]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Table)]
+#[table(schema = "migrate_test", table = "notes")]
+pub struct Note {
+    pub id: i64,
+    pub title: String,
+    pub score: Option<i64>,
+    #[table(rename = "body_text")]
+    #[sqlx(rename = "body_text")]
+    pub body: String,
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_create_and_diff() {
+    let pool = common::setup(&[
+        "DROP SCHEMA IF EXISTS migrate_test CASCADE",
+        "CREATE SCHEMA migrate_test",
+        State::SQL_CREATE_TYPE.unwrap(),
+        // an older version of notes
+        "CREATE TABLE migrate_test.notes (id BIGINT PRIMARY KEY, title VARCHAR(50), score INT, legacy TEXT)",
+    ])
+    .await;
+    let schema = Schema::new().register::<Document>().register::<Note>();
+
+    let diff = schema.diff(&pool).await.unwrap();
+    // the missing table is created, the older one altered
+    let create = Schema::new().register::<Document>().create_sql();
+    let alter = r#"ALTER TABLE "migrate_test"."notes" ALTER COLUMN "title" SET NOT NULL;
+ALTER TABLE "migrate_test"."notes" ALTER COLUMN "score" TYPE INT8 USING "score"::INT8;
+ALTER TABLE "migrate_test"."notes" ADD COLUMN "body_text" TEXT NOT NULL;
+-- ALTER TABLE "migrate_test"."notes" DROP COLUMN "legacy";"#;
+    assert_eq!(diff, format!("{}\n{}", create, alter));
+
+    // the generated statements run, after which only the dropped column is left to review
+    sqlx::raw_sql(&diff).execute(&pool).await.unwrap();
+    assert_eq!(
+        schema.diff(&pool).await.unwrap(),
+        r#"-- ALTER TABLE "migrate_test"."notes" DROP COLUMN "legacy";"#
+    );
+}
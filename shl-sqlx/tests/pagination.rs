@@ -0,0 +1,142 @@
+mod common;
+
+use shl_sqlx::postgres::query::Queryable;
+use shl_sqlx::postgres::{BulkInsertable, Cursor, Page, Paginated, SortOrder, TenantBulkInsertable, TenantPaginated};
+use shl_sqlx::{Insertable, Table};
+use sqlx::types::Uuid;
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "pagination_test_lines", pk("order_id", "line_no"), bulk)]
+pub struct Line {
+    pub order_id: i64,
+    pub line_no: i32,
+    pub qty: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "pagination_test_files", bulk)]
+pub struct File {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "pagination_test_notes", tenant = "tenant_id", bulk)]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+}
+
+/// Follows `next_cursor` until the last page, returning the pages.
+async fn all_pages<T: Paginated>(pool: &PgPool, limit: i64, order: SortOrder) -> Vec<Page<T>> {
+    let mut pages = Vec::new();
+    let mut after: Option<Cursor> = None;
+    loop {
+        let page = T::page_after(pool, after.as_ref(), limit, order).await.unwrap();
+        // cursors survive a round trip through their string form
+        after = page.next_cursor.as_ref().map(|c| c.to_string().parse().unwrap());
+        let last = !page.has_more;
+        pages.push(page);
+        if last {
+            return pages;
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_composite_key() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS pagination_test_lines",
+        "CREATE TABLE pagination_test_lines (order_id BIGINT, line_no INT, qty INT NOT NULL, PRIMARY KEY (order_id, line_no))",
+    ])
+    .await;
+    let lines = (1..=3)
+        .flat_map(|order_id| (1..=3).map(move |line_no| Line { order_id, line_no, qty: 1 }))
+        .collect::<Vec<_>>();
+    Line::insert_many(&pool, &lines).await.unwrap();
+
+    let pages = all_pages::<Line>(&pool, 4, SortOrder::Asc).await;
+    assert_eq!(pages.iter().map(|p| p.items.len()).collect::<Vec<_>>(), [4, 4, 1]);
+    assert!(pages[..2].iter().all(|p| p.has_more && p.next_cursor.is_some()));
+    assert_eq!((pages[2].has_more, &pages[2].next_cursor), (false, &None));
+    assert_eq!(pages.into_iter().flat_map(|p| p.items).collect::<Vec<_>>(), lines);
+
+    // a page that ends exactly at the last row has no next page
+    let pages = all_pages::<Line>(&pool, 3, SortOrder::Desc).await;
+    assert_eq!(pages.iter().map(|p| p.items.len()).collect::<Vec<_>>(), [3, 3, 3]);
+    let keys = pages.iter().flat_map(|p| &p.items).map(|l| (l.order_id, l.line_no)).collect::<Vec<_>>();
+    assert_eq!(keys[..4], [(3, 3), (3, 2), (3, 1), (2, 3)]);
+
+    // filters apply on top of the cursor
+    let cursor = all_pages::<Line>(&pool, 4, SortOrder::Asc).await[0].next_cursor.clone();
+    let page = Line::select()
+        .filter(Line::cols().line_no.eq(3))
+        .fetch_page_after(&pool, cursor.as_ref(), 10, SortOrder::Asc)
+        .await
+        .unwrap();
+    let keys = page.items.iter().map(|l| (l.order_id, l.line_no)).collect::<Vec<_>>();
+    assert_eq!(keys, [(2, 3), (3, 3)]);
+
+    assert!(Line::page_after(&pool, None, 0, SortOrder::Asc).await.unwrap().items.is_empty());
+    assert!(matches!(
+        Line::page_after(&pool, None, -1, SortOrder::Asc).await,
+        Err(sqlx::Error::InvalidArgument(_))
+    ));
+    assert!(matches!(Line::page(&pool, 0, -1, false).await, Err(sqlx::Error::InvalidArgument(_))));
+    let page = Line::page(&pool, 0, i64::MAX, true).await.unwrap();
+    assert_eq!((page.items.len(), page.has_more, page.total), (9, false, Some(9)));
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_uuid_key() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS pagination_test_files",
+        "CREATE TABLE pagination_test_files (id UUID PRIMARY KEY, name TEXT NOT NULL)",
+    ])
+    .await;
+    let files = (1..=5u128)
+        .map(|i| File {
+            id: Uuid::from_u128(i << 64 | i),
+            name: format!("file {}", i),
+        })
+        .collect::<Vec<_>>();
+    File::insert_many(&pool, &files).await.unwrap();
+
+    let pages = all_pages::<File>(&pool, 2, SortOrder::Asc).await;
+    assert_eq!(pages.iter().map(|p| p.items.len()).collect::<Vec<_>>(), [2, 2, 1]);
+    assert_eq!(pages.into_iter().flat_map(|p| p.items).collect::<Vec<_>>(), files);
+
+    let page = File::page_after(&pool, None, i64::MAX, SortOrder::Desc).await.unwrap();
+    assert_eq!(page.items.first(), files.last());
+    assert!(!page.has_more);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_tenant() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS pagination_test_notes",
+        "CREATE TABLE pagination_test_notes (id BIGINT PRIMARY KEY, tenant_id BIGINT NOT NULL)",
+    ])
+    .await;
+    for tenant_id in 1..=2 {
+        let notes = (1..=3)
+            .map(|i| Note {
+                id: tenant_id * 10 + i,
+                tenant_id,
+            })
+            .collect::<Vec<_>>();
+        Note::insert_many(&pool, tenant_id, &notes).await.unwrap();
+    }
+
+    let first = Note::page_after(&pool, 2, None, 2, SortOrder::Asc).await.unwrap();
+    assert_eq!(first.items.iter().map(|n| n.id).collect::<Vec<_>>(), [21, 22]);
+    let rest = Note::page_after(&pool, 2, first.next_cursor.as_ref(), 2, SortOrder::Asc).await.unwrap();
+    assert_eq!((rest.items.iter().map(|n| n.id).collect::<Vec<_>>(), rest.has_more), (vec![23], false));
+
+    let page = Note::page(&pool, 1, 1, 10, true).await.unwrap();
+    assert_eq!((page.items.iter().map(|n| n.id).collect::<Vec<_>>(), page.total), (vec![12, 13], Some(3)));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use shl_sqlx::postgres::query::Queryable;
+use shl_sqlx::postgres::{BulkInsertable, SortOrder};
+use shl_sqlx::{Insertable, Table};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "query_test_accounts", bulk)]
+pub struct Account {
+    pub id: i64,
+    #[table(rename = "mail")]
+    #[sqlx(rename = "mail")]
+    pub email: Option<String>,
+    pub score: i32,
+}
+
+fn account(id: i64, email: Option<&str>, score: i32) -> Account {
+    Account {
+        id,
+        email: email.map(str::to_owned),
+        score,
+    }
+}
+
+fn ids(rows: &[Account]) -> Vec<i64> {
+    rows.iter().map(|a| a.id).collect()
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_select() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS query_test_accounts",
+        "CREATE TABLE query_test_accounts (id BIGINT PRIMARY KEY, mail TEXT, score INT NOT NULL)",
+    ])
+    .await;
+    let accounts = [
+        account(1, Some("a@b.c"), 10),
+        account(2, Some("x@B.C"), 15),
+        account(3, None, 20),
+        account(4, Some("d@e.f"), 5),
+        account(5, None, 15),
+    ];
+    Account::insert_many(&pool, &accounts).await.unwrap();
+    let c = Account::cols();
+
+    let all = Account::select().order_by_pk(SortOrder::Asc).fetch_all(&pool).await.unwrap();
+    assert_eq!(all, accounts);
+
+    let rows = Account::select()
+        .filter(c.email.ilike("%@b.c").or(c.email.is_null()))
+        .filter(!c.id.eq_any([5]))
+        .order_by(c.score.desc())
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(ids(&rows), [3, 2, 1]);
+
+    let rows = Account::select()
+        .filter(c.score.ge(10).and(c.score.lt(20)))
+        .order_by(c.score.asc())
+        .order_by(c.id.desc())
+        .limit(2)
+        .offset(1)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(ids(&rows), [5, 2]);
+
+    // the renamed column is matched under its database name
+    let found = Account::select().filter(c.email.eq("d@e.f".to_owned())).fetch_one(&pool).await.unwrap();
+    assert_eq!(found, accounts[3]);
+    let none = Account::select()
+        .filter(c.email.like("%@B.C"))
+        .filter(c.score.gt(15))
+        .fetch_optional(&pool)
+        .await;
+    assert_eq!(none.unwrap(), None);
+    assert!(matches!(
+        Account::select().filter(c.id.gt(9)).fetch_one(&pool).await,
+        Err(sqlx::Error::RowNotFound)
+    ));
+
+    let select = Account::select().filter(c.email.is_not_null()).filter(c.score.ne(15));
+    assert_eq!(select.count(&pool).await.unwrap(), 2);
+    let page = select.order_by(c.id.asc()).fetch_page(&pool, 0, 1, true).await.unwrap();
+    assert_eq!((ids(&page.items), page.has_more, page.total), (vec![1], true, Some(2)));
+}
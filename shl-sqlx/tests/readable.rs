@@ -0,0 +1,89 @@
+mod common;
+
+use shl_sqlx::postgres::{BulkInsertable, Readable};
+use shl_sqlx::{Insertable, Table};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "readable_test_countries", pk = "code", bulk)]
+pub struct Country {
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "readable_test_labels", pk("entity_id", "locale", "revision"), bulk)]
+pub struct Label {
+    pub entity_id: i64,
+    pub locale: String,
+    pub revision: i32,
+    pub text: String,
+}
+
+fn label(entity_id: i64, locale: &str, revision: i32) -> Label {
+    Label {
+        entity_id,
+        locale: locale.to_owned(),
+        revision,
+        text: format!("{} {} {}", entity_id, locale, revision),
+    }
+}
+
+fn key(l: &Label) -> (i64, String, i32) {
+    (l.entity_id, l.locale.clone(), l.revision)
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_single_key() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS readable_test_countries",
+        "CREATE TABLE readable_test_countries (code TEXT PRIMARY KEY, name TEXT NOT NULL)",
+    ])
+    .await;
+    assert_eq!(Country::count(&pool).await.unwrap(), 0);
+    assert!(Country::find_by_ids(&pool, &[]).await.unwrap().is_empty());
+    let countries = ["PL", "DE", "FR"].map(|code| Country {
+        code: code.to_owned(),
+        name: format!("country {}", code),
+    });
+    Country::insert_many(&pool, &countries).await.unwrap();
+
+    let ids = ["FR", "XX", "PL", "FR"].map(str::to_owned);
+    let mut found = Country::find_by_ids(&pool, &ids).await.unwrap();
+    found.sort_by(|a, b| a.code.cmp(&b.code));
+    assert_eq!(found, [countries[2].clone(), countries[0].clone()]);
+
+    assert_eq!(
+        Country::find_optional_by_id(&pool, "DE".to_owned()).await.unwrap(),
+        Some(countries[1].clone())
+    );
+    assert_eq!(Country::find_optional_by_id(&pool, "XX".to_owned()).await.unwrap(), None);
+    assert!(Country::exists_by_id(&pool, "DE".to_owned()).await.unwrap());
+    assert!(!Country::exists_by_id(&pool, "XX".to_owned()).await.unwrap());
+    assert_eq!(Country::count(&pool).await.unwrap(), 3);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_composite_key() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS readable_test_labels",
+        "CREATE TABLE readable_test_labels (entity_id BIGINT, locale TEXT, revision INT, text TEXT NOT NULL, \
+         PRIMARY KEY (entity_id, locale, revision))",
+    ])
+    .await;
+    let labels = [label(1, "en", 1), label(1, "en", 2), label(1, "pl", 1), label(2, "en", 1)];
+    Label::insert_many(&pool, &labels).await.unwrap();
+
+    // each key must match as a whole, not column by column
+    let ids = [key(&labels[1]), (2, "pl".to_owned(), 1), key(&labels[3]), (1, "pl".to_owned(), 2)];
+    let mut found = Label::find_by_ids(&pool, &ids).await.unwrap();
+    found.sort_by_key(key);
+    assert_eq!(found, [labels[1].clone(), labels[3].clone()]);
+
+    assert_eq!(Label::find_optional_by_id(&pool, key(&labels[2])).await.unwrap(), Some(labels[2].clone()));
+    assert!(Label::exists_by_id(&pool, (2, "en".to_owned(), 1)).await.unwrap());
+    assert!(!Label::exists_by_id(&pool, (2, "en".to_owned(), 2)).await.unwrap());
+    assert_eq!(Label::count(&pool).await.unwrap(), 4);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use shl_sqlx::postgres::BulkInsertable;
+use shl_sqlx::{Insertable, Table};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(
+    table = "relations_test_authors",
+    bulk,
+    has_many(Post, fk = "author_id"),
+    has_many(Post, fk = "editor_id", name = "edited_posts"),
+    has_many(Post, fk = "reviewer_id", name = "reviewed_posts")
+)]
+// clippy takes the repeated `Post` for a duplicated attribute
+#[allow(clippy::duplicated_attributes)]
+pub struct Author {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(
+    table = "relations_test_posts",
+    bulk,
+    belongs_to(Author, fk = "author_id"),
+    belongs_to(Author, fk = "editor_id", name = "editor")
+)]
+#[allow(clippy::duplicated_attributes)]
+pub struct Post {
+    pub id: i64,
+    pub author_id: i64,
+    pub editor_id: Option<i64>,
+    #[table(rename = "reviewer_id")]
+    #[sqlx(rename = "reviewer_id")]
+    pub reviewer: Option<i64>,
+}
+
+fn post(id: i64, author_id: i64, editor_id: Option<i64>, reviewer: Option<i64>) -> Post {
+    Post {
+        id,
+        author_id,
+        editor_id,
+        reviewer,
+    }
+}
+
+fn ids(posts: &[Post]) -> Vec<i64> {
+    let mut ids = posts.iter().map(|p| p.id).collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_relations() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS relations_test_posts",
+        "DROP TABLE IF EXISTS relations_test_authors",
+        "CREATE TABLE relations_test_authors (id BIGINT PRIMARY KEY, name TEXT NOT NULL)",
+        "CREATE TABLE relations_test_posts (id BIGINT PRIMARY KEY, author_id BIGINT NOT NULL REFERENCES relations_test_authors, \
+         editor_id BIGINT REFERENCES relations_test_authors, reviewer_id BIGINT REFERENCES relations_test_authors)",
+    ])
+    .await;
+    let authors = (1..=3)
+        .map(|id| Author {
+            id,
+            name: format!("author {}", id),
+        })
+        .collect::<Vec<_>>();
+    Author::insert_many(&pool, &authors).await.unwrap();
+    let posts = [
+        post(1, 1, None, Some(2)),
+        post(2, 1, Some(2), Some(2)),
+        post(3, 2, Some(1), None),
+        post(4, 1, None, Some(1)),
+    ];
+    Post::insert_many(&pool, &posts).await.unwrap();
+
+    assert_eq!(posts[2].author(&pool).await.unwrap(), authors[1]);
+    assert_eq!(posts[2].editor(&pool).await.unwrap(), Some(authors[0].clone()));
+    assert_eq!(posts[0].editor(&pool).await.unwrap(), None);
+
+    assert_eq!(ids(&authors[0].posts(&pool).await.unwrap()), [1, 2, 4]);
+    assert!(authors[2].posts(&pool).await.unwrap().is_empty());
+
+    // batched loads keep the order of the parents, with an empty list for those without children
+    let loaded = Author::load_posts(&pool, &authors).await.unwrap();
+    assert_eq!(loaded.iter().map(|p| ids(p)).collect::<Vec<_>>(), [vec![1, 2, 4], vec![3], vec![]]);
+    let loaded = Author::load_edited_posts(&pool, &authors[..2]).await.unwrap();
+    assert_eq!(loaded.iter().map(|p| ids(p)).collect::<Vec<_>>(), [vec![3], vec![2]]);
+    // the fk of the renamed field is its column name
+    let loaded = Author::load_reviewed_posts(&pool, &authors).await.unwrap();
+    assert_eq!(loaded.iter().map(|p| ids(p)).collect::<Vec<_>>(), [vec![4], vec![1, 2], vec![]]);
+    assert!(Author::load_posts(&pool, &[]).await.unwrap().is_empty());
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use shl_sqlx::postgres::{DbError, InMemoryRepository, PgRepository, Repository, UpdateError};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "repository_test_accounts", bulk)]
+pub struct Account {
+    pub id: i64,
+    pub owner: String,
+    pub balance: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "repository_test_documents", version = "version", soft_delete = "deleted_at")]
+pub struct Document {
+    pub id: i64,
+    pub body: String,
+    pub version: i64,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "repository_test_profiles", skip_update("id", "handle"))]
+pub struct Profile {
+    pub id: i64,
+    pub handle: String,
+    pub bio: String,
+    #[table(auto_now_add)]
+    pub created_at: DateTime<Utc>,
+}
+
+fn account(id: i64, balance: i64) -> Account {
+    Account {
+        id,
+        owner: format!("owner {}", id),
+        balance,
+    }
+}
+
+/// Service code written against the trait only.
+async fn transfer(repo: &impl Repository<Account>, from: i64, to: i64, amount: i64) -> Result<(), DbError> {
+    let mut from = repo.find_by_id(from).await?;
+    let mut to = repo.find_by_id(to).await?;
+    from.balance -= amount;
+    to.balance += amount;
+    repo.update(&from).await?;
+    repo.update(&to).await?;
+    Ok(())
+}
+
+async fn exercise(repo: &impl Repository<Account>) {
+    assert_eq!(repo.insert(&account(1, 100)).await.unwrap(), 1);
+    assert_eq!(repo.insert_many(&[account(2, 0), account(3, 5)]).await.unwrap(), 2);
+    assert!(matches!(
+        repo.insert(&account(1, 0)).await,
+        Err(DbError::UniqueViolation { constraint }) if constraint == "repository_test_accounts_pkey"
+    ));
+    // a batch with one duplicate inserts nothing
+    assert!(repo.insert_many(&[account(4, 0), account(1, 0)]).await.is_err());
+    assert!(!repo.exists_by_id(4).await.unwrap());
+    assert_eq!(repo.count().await.unwrap(), 3);
+
+    transfer(repo, 1, 2, 30).await.unwrap();
+    assert_eq!(repo.find_by_id(1).await.unwrap().balance, 70);
+    assert_eq!(repo.find_by_id(2).await.unwrap().balance, 30);
+    assert!(matches!(transfer(repo, 1, 9, 1).await, Err(DbError::NotFound)));
+
+    let mut found = repo.find_by_ids(&[3, 1, 9]).await.unwrap();
+    found.sort_by_key(|a| a.id);
+    assert_eq!(found.iter().map(|a| a.id).collect::<Vec<_>>(), [1, 3]);
+
+    assert_eq!(repo.update(&account(9, 0)).await.unwrap(), 0);
+    assert!(matches!(repo.update_returning(&account(9, 0)).await, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(repo.update_returning(&account(3, 8)).await.unwrap(), account(3, 8));
+
+    assert_eq!(repo.delete_by_id(3).await.unwrap(), 1);
+    assert_eq!(repo.delete_by_id(3).await.unwrap(), 0);
+    assert_eq!(repo.find_optional_by_id(3).await.unwrap(), None);
+}
+
+/// Changes every field of the stored profile, returning the updated row and the row read back.
+async fn update_profile(repo: &impl Repository<Profile>) -> (Profile, Profile) {
+    let mut profile = repo.find_by_id(1).await.unwrap();
+    profile.handle = "changed".to_owned();
+    profile.bio = "changed".to_owned();
+    profile.created_at = DateTime::UNIX_EPOCH;
+    let returned = repo.update_returning(&profile).await.unwrap();
+    (returned, repo.find_by_id(1).await.unwrap())
+}
+
+#[tokio::test]
+async fn test_in_memory() {
+    let repo = InMemoryRepository::new();
+    exercise(&repo).await;
+    assert_eq!(repo.rows().len(), 2);
+
+    let seeded = InMemoryRepository::with_rows([account(1, 10), account(2, 0)]);
+    transfer(&seeded, 1, 2, 10).await.unwrap();
+    assert_eq!(seeded.find_by_id(2).await.unwrap().balance, 10);
+}
+
+#[tokio::test]
+async fn test_in_memory_unsupported() {
+    let doc = Document {
+        id: 1,
+        body: "a".to_owned(),
+        version: 1,
+        deleted_at: None,
+    };
+    let repo = InMemoryRepository::with_rows([doc.clone()]);
+    assert!(matches!(
+        repo.update(&doc).await,
+        Err(UpdateError::Sqlx(sqlx::Error::InvalidArgument(e))) if e.contains("\"version\"")
+    ));
+    assert!(matches!(
+        repo.update_returning(&doc).await,
+        Err(UpdateError::Sqlx(sqlx::Error::InvalidArgument(_)))
+    ));
+    assert!(matches!(repo.delete_by_id(1).await, Err(DbError::Other(sqlx::Error::InvalidArgument(_)))));
+    assert_eq!(repo.rows(), [doc]);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_postgres() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS repository_test_accounts",
+        "CREATE TABLE repository_test_accounts (id BIGINT PRIMARY KEY, owner TEXT NOT NULL, balance BIGINT NOT NULL)",
+    ])
+    .await;
+    exercise(&PgRepository::new(pool)).await;
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_update_parity() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS repository_test_profiles",
+        "CREATE TABLE repository_test_profiles (id BIGINT PRIMARY KEY, handle TEXT NOT NULL, bio TEXT NOT NULL, \
+         created_at TIMESTAMPTZ NOT NULL DEFAULT now())",
+    ])
+    .await;
+    let pg = PgRepository::new(pool);
+    let profile = Profile {
+        id: 1,
+        handle: "alice".to_owned(),
+        bio: String::new(),
+        created_at: DateTime::UNIX_EPOCH,
+    };
+    let stored = pg.insert_returning(&profile).await.unwrap();
+    let memory = InMemoryRepository::with_rows([stored.clone()]);
+
+    // only `bio` is written: `handle` is in skip_update and `created_at` is auto_now_add
+    let (returned, found) = update_profile(&pg).await;
+    assert_eq!(
+        returned,
+        Profile {
+            bio: "changed".to_owned(),
+            ..stored
+        }
+    );
+    assert_eq!(found, returned);
+    assert_eq!(update_profile(&memory).await, (returned, found));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use shl_sqlx::postgres::{Insertable, Readable, Updatable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "returning_test_tickets", insert_skip("id", "status"), skip_update("id"))]
+pub struct Ticket {
+    pub id: i64,
+    pub title: String,
+    pub status: String,
+}
+
+#[test]
+fn test_sql() {
+    assert_eq!(
+        Ticket::SQL_INSERT_RETURNING,
+        r#"INSERT INTO "public"."returning_test_tickets" ("title" ) VALUES ($1) RETURNING "id", "title", "status""#
+    );
+    assert_eq!(
+        Ticket::SQL_DELETE_BY_PK_RETURNING,
+        r#"DELETE FROM "public"."returning_test_tickets" WHERE "id" = $1 RETURNING "id", "title", "status""#
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_returning() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS returning_test_tickets",
+        "CREATE TABLE returning_test_tickets (id BIGSERIAL PRIMARY KEY, title TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'open')",
+    ])
+    .await;
+    let draft = Ticket {
+        id: 0,
+        title: "a".to_owned(),
+        status: String::new(),
+    };
+
+    // the database fills in the skipped columns
+    let first = draft.insert_returning(&pool).await.unwrap();
+    let second = draft.insert_returning(&pool).await.unwrap();
+    assert_eq!((first.id, first.title.as_str(), first.status.as_str()), (1, "a", "open"));
+    assert_eq!(second.id, 2);
+    assert_eq!(Ticket::find_by_id(&pool, 1).await.unwrap(), first);
+
+    let closed = Ticket {
+        status: "closed".to_owned(),
+        ..first.clone()
+    };
+    assert_eq!(closed.update_returning(&pool).await.unwrap(), closed);
+    let missing = Ticket { id: 9, ..closed.clone() };
+    assert!(matches!(missing.update_returning(&pool).await, Err(sqlx::Error::RowNotFound)));
+
+    assert_eq!(Ticket::delete_returning(&pool, 1).await.unwrap(), closed);
+    assert!(matches!(Ticket::delete_returning(&pool, 1).await, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(Ticket::count(&pool).await.unwrap(), 1);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use shl_sqlx::Table;
+use shl_sqlx::postgres::Readable;
+use shl_sqlx::postgres::schema::Snapshot;
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "schema_test.account_kind")]
+pub enum AccountKind {
+    Personal,
+}
+
+/// The `#[table(verify)]` type of `tests/ui/pass/verify.rs`, in a schema of its own.
+#[derive(Debug, Clone, PartialEq, FromRow, Table)]
+#[table(schema = "schema_test", table = "accounts")]
+pub struct Account {
+    pub id: i64,
+    #[table(rename = "mail")]
+    #[sqlx(rename = "mail")]
+    pub email: Option<String>,
+    pub score: Option<i32>,
+    pub tags: Vec<String>,
+    pub kind: AccountKind,
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_snapshot() {
+    let pool = common::setup(&[
+        "DROP SCHEMA IF EXISTS schema_test CASCADE",
+        "CREATE SCHEMA schema_test",
+        "CREATE TYPE schema_test.account_kind AS ENUM ('Personal')",
+        "CREATE TABLE schema_test.accounts (id BIGINT PRIMARY KEY, mail TEXT, score INT NOT NULL, \
+         tags TEXT[] NOT NULL, kind schema_test.account_kind NOT NULL)",
+    ])
+    .await;
+
+    // the snapshot the derive tests verify against is what a real table produces
+    let snapshot = Snapshot::fetch(&pool, &["schema_test"]).await.unwrap();
+    let fetched: serde_json::Value = serde_json::from_str(&snapshot.to_json()).unwrap();
+    let fixture: serde_json::Value = serde_json::from_str(include_str!("ui/schema.json")).unwrap();
+    assert_eq!(fetched["tables"]["schema_test.accounts"], fixture["tables"]["public.accounts"]);
+    assert_eq!(fetched["tables"].as_object().unwrap().len(), 1);
+
+    let path = std::env::temp_dir().join(format!("shl-sqlx-schema-{}.json", std::process::id()));
+    snapshot.write(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), snapshot.to_json());
+    std::fs::remove_file(&path).unwrap();
+
+    // and the field types it accepts read the columns back
+    sqlx::query("INSERT INTO schema_test.accounts VALUES (1, NULL, 3, '{a}', 'Personal')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let account = Account {
+        id: 1,
+        email: None,
+        score: Some(3),
+        tags: vec!["a".to_owned()],
+        kind: AccountKind::Personal,
+    };
+    assert_eq!(Account::find_by_id(&pool, 1).await.unwrap(), account);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use shl_sqlx::postgres::query::Queryable;
+use shl_sqlx::postgres::{Insertable, Readable, SoftDeletable};
+use shl_sqlx::{Insertable, Table};
+use sqlx::FromRow;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "soft_delete_test_users", soft_delete = "deleted_at")]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+fn user(id: i64) -> User {
+    User {
+        id,
+        name: format!("user {}", id),
+        deleted_at: None,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_soft_delete() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS soft_delete_test_users",
+        "CREATE TABLE soft_delete_test_users (id BIGINT PRIMARY KEY, name TEXT NOT NULL, deleted_at TIMESTAMPTZ)",
+    ])
+    .await;
+    for id in 1..=3 {
+        user(id).insert(&pool).await.unwrap();
+    }
+
+    assert_eq!(User::delete_by_id(&pool, 1).await.unwrap(), 1);
+    // deleting again is a no-op that keeps the first timestamp
+    let deleted = User::find_by_id_with_deleted(&pool, 1).await.unwrap();
+    assert!(deleted.deleted_at.is_some());
+    assert_eq!(User::delete_by_id(&pool, 1).await.unwrap(), 0);
+    assert_eq!(User::find_by_id_with_deleted(&pool, 1).await.unwrap(), deleted);
+
+    // every read skips the deleted row
+    assert!(matches!(User::find_by_id(&pool, 1).await, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(User::find_optional_by_id(&pool, 1).await.unwrap(), None);
+    assert!(!User::exists_by_id(&pool, 1).await.unwrap());
+    assert_eq!(User::find_by_ids(&pool, &[1, 2]).await.unwrap(), [user(2)]);
+    assert_eq!(User::count(&pool).await.unwrap(), 2);
+    assert_eq!(User::select().fetch_all(&pool).await.unwrap().len(), 2);
+    assert_eq!(User::select().with_deleted().fetch_all(&pool).await.unwrap().len(), 3);
+
+    assert_eq!(User::restore_by_id(&pool, 1).await.unwrap(), 1);
+    assert_eq!(User::restore_by_id(&pool, 1).await.unwrap(), 0);
+    assert_eq!(User::find_by_id(&pool, 1).await.unwrap(), user(1));
+
+    // purging removes live and deleted rows alike
+    User::delete_by_id(&pool, 2).await.unwrap();
+    assert_eq!(User::purge_by_id(&pool, 2).await.unwrap(), 1);
+    assert_eq!(User::purge_by_id(&pool, 3).await.unwrap(), 1);
+    assert!(User::find_by_id_with_deleted(&pool, 2).await.is_err());
+    assert_eq!(User::select().with_deleted().count(&pool).await.unwrap(), 1);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use shl_sqlx::postgres::query::Queryable;
+use shl_sqlx::postgres::{TenantBulkInsertable, TenantInsertable, TenantReadable, TenantSoftDeletable, TenantUpdatable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+use sqlx::types::chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "tenant_test_notes", tenant = "tenant_id", soft_delete = "deleted_at", skip_update("id"), bulk)]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+    pub body: String,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// The tenant field is left at 0: the tenant argument is what gets written.
+fn note(id: i64, body: &str) -> Note {
+    Note {
+        id,
+        tenant_id: 0,
+        body: body.to_owned(),
+        deleted_at: None,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_scoping() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS tenant_test_notes",
+        "CREATE TABLE tenant_test_notes (id BIGINT PRIMARY KEY, tenant_id BIGINT NOT NULL, body TEXT NOT NULL, deleted_at TIMESTAMPTZ)",
+    ])
+    .await;
+    let stored = note(1, "a").insert_returning(&pool, 1).await.unwrap();
+    assert_eq!(stored.tenant_id, 1);
+    Note::insert_many(&pool, 2, &[note(2, "b"), note(3, "c")]).await.unwrap();
+
+    // reads only see the rows of their tenant
+    assert_eq!(Note::find_by_id(&pool, 1, 1).await.unwrap(), stored);
+    assert_eq!(Note::find_optional_by_id(&pool, 2, 1).await.unwrap(), None);
+    assert!(!Note::exists_by_id(&pool, 1, 2).await.unwrap());
+    assert_eq!(Note::find_by_ids(&pool, 2, &[1, 2, 3]).await.unwrap().len(), 2);
+    assert_eq!((Note::count(&pool, 1).await.unwrap(), Note::count(&pool, 2).await.unwrap()), (1, 2));
+    assert_eq!(Note::select().tenant(2).count(&pool).await.unwrap(), 2);
+    assert!(Note::select().fetch_all(&pool).await.unwrap().is_empty());
+
+    // and writes can't reach another tenant's rows
+    assert_eq!(note(2, "x").update(&pool, 1).await.unwrap(), 0);
+    let patch = || NotePatch {
+        body: Some("x".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(Note::update_patch(&pool, 1, 2, (), patch()).await.unwrap(), 0);
+    assert_eq!(note(2, "x").upsert(&pool, 1).await.unwrap(), 0);
+    assert_eq!(Note::delete_by_id(&pool, 1, 2).await.unwrap(), 0);
+    assert_eq!(Note::purge_by_id(&pool, 1, 3).await.unwrap(), 0);
+    assert_eq!(Note::find_by_id(&pool, 2, 2).await.unwrap().body, "b");
+
+    assert_eq!(note(2, "d").update(&pool, 2).await.unwrap(), 1);
+    assert_eq!(Note::update_patch(&pool, 2, 3, (), patch()).await.unwrap(), 1);
+    assert_eq!(note(2, "e").upsert(&pool, 2).await.unwrap(), 1);
+    assert_eq!(Note::find_by_id(&pool, 2, 2).await.unwrap().body, "e");
+
+    assert_eq!(Note::delete_by_id(&pool, 2, 3).await.unwrap(), 1);
+    assert!(Note::find_by_id_with_deleted(&pool, 1, 3).await.is_err());
+    assert_eq!(Note::restore_by_id(&pool, 2, 3).await.unwrap(), 1);
+    assert_eq!(Note::purge_by_id(&pool, 2, 3).await.unwrap(), 1);
+    assert_eq!(Note::count(&pool, 2).await.unwrap(), 1);
+}
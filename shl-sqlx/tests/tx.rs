@@ -0,0 +1,142 @@
+mod common;
+
+use shl_sqlx::postgres::tx::{IsolationLevel, RetryPolicy, with_transaction, with_transaction_retry};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+const SERIALIZATION_FAILURE: &str = "DO $$ BEGIN RAISE EXCEPTION 'conflict' USING ERRCODE = 'serialization_failure'; END $$";
+
+const FAST: RetryPolicy = RetryPolicy {
+    max_attempts: 3,
+    initial_backoff: Duration::from_millis(1),
+    max_backoff: Duration::from_millis(5),
+};
+
+async fn setup(table: &str) -> PgPool {
+    common::setup(&[
+        &format!("DROP TABLE IF EXISTS {table}"),
+        &format!("CREATE TABLE {table} (id BIGINT PRIMARY KEY)"),
+    ])
+    .await
+}
+
+async fn count(pool: &PgPool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT count(*) FROM {table}"))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_commit() {
+    let pool = setup("tx_test_commit").await;
+    let level: String = with_transaction(&pool, IsolationLevel::Serializable, |tx| {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO tx_test_commit VALUES (1)").execute(&mut **tx).await?;
+            sqlx::query_scalar("SHOW transaction_isolation").fetch_one(&mut **tx).await
+        })
+    })
+    .await
+    .unwrap();
+    assert_eq!(level, "serializable");
+    assert_eq!(count(&pool, "tx_test_commit").await, 1);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_rollback_on_error() {
+    let pool = setup("tx_test_rollback").await;
+    let res: Result<(), sqlx::Error> = with_transaction(&pool, IsolationLevel::ReadCommitted, |tx| {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO tx_test_rollback VALUES (1)").execute(&mut **tx).await?;
+            Err(sqlx::Error::RowNotFound)
+        })
+    })
+    .await;
+    assert!(matches!(res, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(count(&pool, "tx_test_rollback").await, 0);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_retry_on_serialization_failure() {
+    let pool = setup("tx_test_retry").await;
+    let attempts = AtomicU32::new(0);
+    with_transaction_retry(&pool, IsolationLevel::Serializable, FAST, |tx| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        Box::pin(async move {
+            sqlx::query("INSERT INTO tx_test_retry VALUES (1)").execute(&mut **tx).await?;
+            if attempt < 3 {
+                sqlx::query(SERIALIZATION_FAILURE).execute(&mut **tx).await?;
+            }
+            Ok::<_, sqlx::Error>(())
+        })
+    })
+    .await
+    .unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(count(&pool, "tx_test_retry").await, 1);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_gives_up_after_max_attempts() {
+    let pool = setup("tx_test_give_up").await;
+    let attempts = AtomicU32::new(0);
+    let res: Result<(), sqlx::Error> = with_transaction_retry(&pool, IsolationLevel::Serializable, FAST, |tx| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            sqlx::query(SERIALIZATION_FAILURE).execute(&mut **tx).await?;
+            Ok(())
+        })
+    })
+    .await;
+    let code = res.unwrap_err().as_database_error().and_then(|e| e.code()).map(|c| c.into_owned());
+    assert_eq!(code.as_deref(), Some("40001"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_no_retry_on_other_errors() {
+    let pool = setup("tx_test_no_retry").await;
+    let attempts = AtomicU32::new(0);
+    let res: Result<(), sqlx::Error> = with_transaction_retry(&pool, IsolationLevel::Serializable, FAST, |tx| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            sqlx::query("INSERT INTO tx_test_no_retry VALUES (1), (1)").execute(&mut **tx).await?;
+            Ok(())
+        })
+    })
+    .await;
+    assert!(res.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_concurrent_serializable_conflict() {
+    let pool = setup("tx_test_conflict").await;
+    sqlx::query("INSERT INTO tx_test_conflict VALUES (0)").execute(&pool).await.unwrap();
+    // every transaction reads the current maximum and inserts the next id, which conflicts
+    // under SERIALIZABLE until the loser is retried
+    let run = || {
+        with_transaction(&pool, IsolationLevel::Serializable, |tx| {
+            Box::pin(async move {
+                let max: i64 = sqlx::query_scalar("SELECT max(id) FROM tx_test_conflict").fetch_one(&mut **tx).await?;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                sqlx::query("INSERT INTO tx_test_conflict VALUES ($1)")
+                    .bind(max + 1)
+                    .execute(&mut **tx)
+                    .await?;
+                Ok::<_, sqlx::Error>(())
+            })
+        })
+    };
+    let (a, b) = tokio::join!(run(), run());
+    a.unwrap();
+    b.unwrap();
+    assert_eq!(count(&pool, "tx_test_conflict").await, 3);
+}
@@ -0,0 +1,11 @@
+use shl_sqlx::{Insertable, Table};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table, Insertable)]
+#[table(audit, bulk)]
+pub struct Post {
+    pub id: i64,
+    pub title: String,
+}
+
+fn main() {}
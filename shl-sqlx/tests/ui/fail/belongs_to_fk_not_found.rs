@@ -0,0 +1,16 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+pub struct Author {
+    pub id: i64,
+}
+
+#[derive(FromRow, Table)]
+#[table(belongs_to(Author, fk = "writer_id"))]
+pub struct Post {
+    pub id: i64,
+    pub author_id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use shl_sqlx::Table;
+
+pub struct Encrypted<T>(pub T);
+
+#[derive(Table)]
+#[table(pk = "email")]
+pub struct User {
+    #[table(encrypted)]
+    pub email: Encrypted<String>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+pub struct User {
+    pub id: i64,
+    #[table(encrypted)]
+    pub email: String,
+}
+
+fn main() {}
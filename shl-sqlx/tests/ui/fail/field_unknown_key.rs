@@ -0,0 +1,11 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+pub struct Post {
+    pub id: i64,
+    #[table(auto_now)]
+    pub created_at: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(pk("tenant_id", "id"), has_many(Post, fk = "author_id"))]
+pub struct Author {
+    pub tenant_id: i64,
+    pub id: i64,
+}
+
+#[derive(FromRow, Table)]
+pub struct Post {
+    pub id: i64,
+    pub author_id: i64,
+}
+
+fn main() {}
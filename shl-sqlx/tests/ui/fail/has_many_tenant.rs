@@ -0,0 +1,18 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(has_many(Note, fk = "author_id"))]
+pub struct Author {
+    pub id: i64,
+}
+
+#[derive(FromRow, Table)]
+#[table(tenant = "tenant_id")]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+    pub author_id: i64,
+}
+
+fn main() {}
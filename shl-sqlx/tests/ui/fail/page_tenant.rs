@@ -0,0 +1,16 @@
+use shl_sqlx::Table;
+use shl_sqlx::postgres::{Paginated, SortOrder};
+use sqlx::{FromRow, PgPool};
+
+#[derive(FromRow, Table)]
+#[table(tenant = "tenant_id")]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+}
+
+async fn list(pool: &PgPool) {
+    let _ = Note::page_after(pool, None, 10, SortOrder::Asc).await;
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use shl_sqlx::PgComposite;
+
+#[derive(PgComposite)]
+pub struct Point(pub f64, pub f64);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use shl_sqlx::PgEnum;
+
+#[derive(PgEnum)]
+pub enum Plan {
+    Free,
+    Custom(String),
+}
+
+fn main() {}
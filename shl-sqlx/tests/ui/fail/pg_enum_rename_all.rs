@@ -0,0 +1,9 @@
+use shl_sqlx::PgEnum;
+
+#[derive(PgEnum)]
+#[pg_enum(rename_all = "Title Case")]
+pub enum Plan {
+    Free,
+}
+
+fn main() {}
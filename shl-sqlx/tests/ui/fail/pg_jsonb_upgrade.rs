@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use shl_sqlx::PgJsonb;
+
+#[derive(Serialize, Deserialize, PgJsonb)]
+#[pg_jsonb(version = 1, upgrade(from = 1, with = "upgrade"))]
+pub struct Profile {
+    pub name: String,
+}
+
+fn upgrade(json: serde_json::Value) -> Result<serde_json::Value, String> {
+    Ok(json)
+}
+
+fn main() {}
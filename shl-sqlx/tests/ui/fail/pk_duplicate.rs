@@ -0,0 +1,11 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(pk("tenant_id", "entity_id", "tenant_id"))]
+pub struct Label {
+    pub tenant_id: i64,
+    pub entity_id: i64,
+}
+
+fn main() {}
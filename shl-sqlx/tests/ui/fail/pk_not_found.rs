@@ -0,0 +1,12 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(pk("tenant_id", "entity_id", "locale"))]
+pub struct Label {
+    pub tenant_id: i64,
+    pub entity_id: i64,
+    pub text: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use shl_sqlx::postgres::TableMeta;
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(pk("tenant_id", "entity_id", "locale"))]
+pub struct Label {
+    pub tenant_id: i64,
+    pub entity_id: i64,
+    pub locale: String,
+}
+
+fn main() {
+    let _: <Label as TableMeta>::Id = (1, 2);
+}
@@ -0,0 +1,13 @@
+use shl_sqlx::Table;
+use shl_sqlx::postgres::Queryable;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+pub struct User {
+    pub id: i64,
+    pub email: String,
+}
+
+fn main() {
+    let _ = User::select().filter(User::cols().emial.eq("a@b.c".to_owned()));
+}
@@ -0,0 +1,11 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(soft_delete = "removed_at")]
+pub struct User {
+    pub id: i64,
+    pub deleted_at: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(tenant = "org_id")]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use shl_sqlx::Table;
+use shl_sqlx::postgres::Readable;
+use sqlx::{FromRow, PgPool};
+
+#[derive(FromRow, Table)]
+#[table(tenant = "tenant_id")]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+}
+
+async fn load(pool: &PgPool) {
+    let _ = Note::find_by_id(pool, 1).await;
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(table = "accounts", verify)]
+pub struct Account {
+    pub id: i32,
+    pub email: Option<String>,
+    pub mail: String,
+    pub tags: Vec<i64>,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(schema = "billing", verify)]
+pub struct Account {
+    pub id: i64,
+}
+
+fn main() {}
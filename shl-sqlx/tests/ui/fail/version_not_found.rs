@@ -0,0 +1,12 @@
+use shl_sqlx::Updatable;
+use sqlx::FromRow;
+
+#[derive(FromRow, Updatable)]
+#[table(version = "revision")]
+pub struct Doc {
+    pub id: i64,
+    pub body: String,
+    pub version: i32,
+}
+
+fn main() {}
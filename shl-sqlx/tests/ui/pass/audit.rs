@@ -0,0 +1,30 @@
+use shl_sqlx::postgres::{Insertable, Readable, TableMeta, Updatable, Upsertable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table, Insertable, Updatable)]
+#[table(audit = "history.changes", skip_update("id"))]
+pub struct Post {
+    pub id: i64,
+    pub title: String,
+}
+
+fn main() {
+    assert_eq!(Post::AUDIT_TABLE, Some(r#""history"."changes""#));
+    assert_eq!(
+        Post::SQL_INSERT,
+        r#"WITH "__new" AS (INSERT INTO "public"."posts" ("id", "title" ) VALUES ($1, $2) RETURNING "id", "title") INSERT INTO "history"."changes" ("table_name", "operation", "pk", "old_values", "new_values", "actor_id") SELECT 'public.posts', 'INSERT', jsonb_build_object('id', "__new"."id"), NULL::jsonb, to_jsonb("__new"), NULLIF(current_setting('shl_sqlx.actor_id', true), '') FROM "__new""#
+    );
+    assert_eq!(
+        Post::SQL_UPDATE_RETURNING,
+        r#"WITH "__old" AS (SELECT "id", "title" FROM "public"."posts" WHERE "id" = $2), "__new" AS (UPDATE "public"."posts" SET "title" = $1 WHERE "id" = $2 RETURNING "id", "title"), "__audit" AS (INSERT INTO "history"."changes" ("table_name", "operation", "pk", "old_values", "new_values", "actor_id") SELECT 'public.posts', 'UPDATE', jsonb_build_object('id', "__new"."id"), to_jsonb("__old"), to_jsonb("__new"), NULLIF(current_setting('shl_sqlx.actor_id', true), '') FROM "__new" JOIN "__old" USING ("id")) SELECT "id", "title" FROM "__new""#
+    );
+    assert_eq!(
+        Post::SQL_UPSERT,
+        r#"WITH "__old" AS (SELECT "id", "title" FROM "public"."posts" WHERE "id" = $1), "__new" AS (INSERT INTO "public"."posts" ("id", "title" ) VALUES ($1, $2) ON CONFLICT ("id") DO UPDATE SET "title" = EXCLUDED."title" RETURNING "id", "title") INSERT INTO "history"."changes" ("table_name", "operation", "pk", "old_values", "new_values", "actor_id") SELECT 'public.posts', CASE WHEN "__old"."id" IS NULL THEN 'INSERT' ELSE 'UPDATE' END, jsonb_build_object('id', "__new"."id"), to_jsonb("__old"), to_jsonb("__new"), NULLIF(current_setting('shl_sqlx.actor_id', true), '') FROM "__new" LEFT JOIN "__old" USING ("id")"#
+    );
+    assert_eq!(
+        Post::SQL_DELETE_BY_PK,
+        r#"WITH "__new" AS (DELETE FROM "public"."posts" WHERE "id" = $1 RETURNING "id", "title") INSERT INTO "history"."changes" ("table_name", "operation", "pk", "old_values", "new_values", "actor_id") SELECT 'public.posts', 'DELETE', jsonb_build_object('id', "__new"."id"), to_jsonb("__new"), NULL::jsonb, NULLIF(current_setting('shl_sqlx.actor_id', true), '') FROM "__new""#
+    );
+}
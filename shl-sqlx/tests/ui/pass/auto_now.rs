@@ -0,0 +1,37 @@
+use shl_sqlx::postgres::{BulkInsertable, Insertable, Updatable, Upsertable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table, Insertable, Updatable)]
+#[table(bulk)]
+pub struct Post {
+    pub id: i64,
+    pub title: String,
+    #[table(auto_now_add)]
+    pub created_at: String,
+    #[table(rename = "modified_at", auto_now_update)]
+    pub updated_at: String,
+}
+
+fn main() {
+    assert_eq!(
+        Post::SQL_INSERT,
+        r#"INSERT INTO "public"."posts" ("id", "title", "created_at", "modified_at" ) VALUES ($1, $2, now(), now())"#
+    );
+    assert_eq!(
+        Post::sql_insert_many(),
+        r#"INSERT INTO "public"."posts" ("id", "title", "created_at", "modified_at") SELECT "id", "title", now(), now() FROM UNNEST($1::INT8[], $2::TEXT[]) AS "__rows"("id", "title")"#
+    );
+    assert_eq!(
+        Post::SQL_UPSERT,
+        r#"INSERT INTO "public"."posts" ("id", "title", "created_at", "modified_at" ) VALUES ($1, $2, now(), now()) ON CONFLICT ("id") DO UPDATE SET "title" = EXCLUDED."title", "modified_at" = EXCLUDED."modified_at""#
+    );
+    assert_eq!(
+        Post::SQL_UPDATE,
+        r#"UPDATE "public"."posts" SET "title" = $1, "modified_at" = now() WHERE "id" = $2"#
+    );
+    // timestamp columns are never part of a patch
+    let _ = PostPatch {
+        title: Some("hello".to_owned()),
+    };
+}
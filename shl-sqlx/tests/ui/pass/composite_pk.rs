@@ -0,0 +1,49 @@
+use shl_sqlx::postgres::{Readable, TableMeta};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table, Insertable, Updatable)]
+#[table(table = "translations", pk("tenant_id", "entity_id", "locale", "version"))]
+pub struct Translation {
+    pub tenant_id: i64,
+    pub entity_id: i64,
+    pub locale: String,
+    pub version: i32,
+    pub body: String,
+}
+
+#[derive(FromRow, Table)]
+#[table(pk("tenant_id", "entity_id", "locale"))]
+pub struct Label {
+    pub tenant_id: i64,
+    pub entity_id: i64,
+    pub locale: String,
+    pub text: String,
+}
+
+#[derive(FromRow, Table)]
+#[table(pk = "code")]
+pub struct Country {
+    pub code: String,
+    pub name: String,
+}
+
+fn main() {
+    let _: <Translation as TableMeta>::Id = (1, 2, "en".to_owned(), 3);
+    let _: <Label as TableMeta>::Id = (1, 2, "en".to_owned());
+    let _: <Country as TableMeta>::Id = "PL".to_owned();
+    let _ = TranslationPatch {
+        body: Some("hello".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(
+        Label::SQL_SELECT_BY_PKS,
+        r#"SELECT "tenant_id", "entity_id", "locale", "text" FROM "public"."labels" WHERE ("tenant_id", "entity_id", "locale") IN (SELECT * FROM UNNEST($1, $2, $3))"#
+    );
+    assert_eq!(Country::SQL_SELECT_BY_PKS, r#"SELECT "code", "name" FROM "public"."countrys" WHERE "code" = ANY($1)"#);
+    assert_eq!(
+        Country::SQL_EXISTS_BY_PK,
+        r#"SELECT EXISTS (SELECT 1 FROM "public"."countrys" WHERE "code" = $1)"#
+    );
+    assert_eq!(Country::SQL_COUNT, r#"SELECT count(*) FROM "public"."countrys""#);
+}
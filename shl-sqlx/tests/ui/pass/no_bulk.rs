@@ -0,0 +1,21 @@
+use shl_sqlx::postgres::{Insertable, Updatable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+/// Has no `PgHasArrayType`, so it can't be bound by `insert_many`.
+#[derive(sqlx::Type)]
+#[sqlx(transparent, no_pg_array)]
+pub struct Money(i64);
+
+// Without `#[table(bulk)]` no field has to be bindable as an array.
+#[derive(FromRow, Table, Insertable, Updatable)]
+pub struct Invoice {
+    pub id: i64,
+    pub total: Money,
+    pub tags: Vec<String>,
+}
+
+fn main() {
+    assert!(Invoice::SQL_INSERT.starts_with(r#"INSERT INTO "public"."invoices""#));
+    assert!(Invoice::SQL_UPDATE.contains(r#""tags" = $"#));
+}
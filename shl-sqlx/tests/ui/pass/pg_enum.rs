@@ -0,0 +1,49 @@
+use shl_sqlx::PgEnum;
+use shl_sqlx::postgres::PgEnum as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+#[pg_enum(type_name = "integration_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IntegrationKind {
+    Google,
+    GitHub,
+    #[pg_enum(rename = "MS")]
+    Microsoft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+#[pg_enum(rename_all = "kebab-case")]
+pub enum Status {
+    Active,
+    HTTPError,
+    OAuth2Pending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PgEnum)]
+pub enum Plain {
+    Free,
+    Pro,
+}
+
+fn main() {
+    assert_eq!(IntegrationKind::TYPE_NAME, "integration_kind");
+    assert_eq!(
+        IntegrationKind::ALL_VARIANTS,
+        [IntegrationKind::Google, IntegrationKind::GitHub, IntegrationKind::Microsoft]
+    );
+    assert_eq!(IntegrationKind::LABELS, ["GOOGLE", "GIT_HUB", "MS"]);
+    assert_eq!(
+        IntegrationKind::SQL_CREATE_TYPE,
+        Some(r#"CREATE TYPE "integration_kind" AS ENUM ('GOOGLE', 'GIT_HUB', 'MS')"#)
+    );
+    assert_eq!(IntegrationKind::Microsoft.to_string(), "MS");
+    assert_eq!("GIT_HUB".parse::<IntegrationKind>(), Ok(IntegrationKind::GitHub));
+    assert_eq!(
+        "GitHub".parse::<IntegrationKind>().unwrap_err().to_string(),
+        "unknown IntegrationKind variant 'GitHub'"
+    );
+
+    assert_eq!(Status::TYPE_NAME, "varchar");
+    assert_eq!(Status::SQL_CREATE_TYPE, None);
+    assert_eq!(Status::LABELS, ["active", "http-error", "o-auth2-pending"]);
+    assert_eq!(Plain::LABELS, ["Free", "Pro"]);
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use shl_sqlx::PgJsonb;
+use shl_sqlx::postgres::PgJsonb as _;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, PgJsonb)]
+pub struct Tags {
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, PgJsonb)]
+#[pg_jsonb(type_name = "json", version = 1, upgrade(from = 0, with = "profile_v0"))]
+pub struct Profile {
+    pub display_name: String,
+}
+
+fn profile_v0(json: Value) -> Result<Value, String> {
+    let name = json.get("name").cloned().ok_or("missing name")?;
+    Ok(json!({ "display_name": name }))
+}
+
+fn main() {
+    assert_eq!(<Tags as sqlx::Type<sqlx::Postgres>>::type_info().to_string(), "jsonb");
+    assert_eq!(<Profile as sqlx::Type<sqlx::Postgres>>::type_info().to_string(), "json");
+    assert_eq!(Tags::VERSION, None);
+    assert_eq!(Profile::VERSION, Some(1));
+
+    let tags = Tags::try_from(json!({ "tags": ["a"] })).unwrap();
+    assert_eq!(Value::try_from(tags).unwrap(), json!({ "tags": ["a"] }));
+    assert!(Tags::try_from(json!([1])).is_err());
+
+    let profile = Profile::try_from(json!({ "name": "Ann" })).unwrap();
+    assert_eq!(profile.display_name, "Ann");
+    assert_eq!(profile.to_jsonb().unwrap(), json!({ "$v": 1, "$data": { "display_name": "Ann" } }));
+}
@@ -0,0 +1,36 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(
+    has_many(Post, fk = "author_id"),
+    has_many(Post, fk = "editor_id", name = "edited_posts"),
+    has_many(Post, fk = "reviewer_id", name = "reviewed_posts")
+)]
+pub struct Author {
+    pub id: i64,
+}
+
+#[derive(FromRow, Table)]
+#[table(belongs_to(Author, fk = "author_id"), belongs_to(Author, fk = "editor_id", name = "editor"))]
+pub struct Post {
+    pub id: i64,
+    pub author_id: i64,
+    pub editor_id: Option<i64>,
+    #[table(rename = "reviewer_id")]
+    pub reviewer: Option<i64>,
+}
+
+async fn load(pool: &sqlx::PgPool, authors: &[Author], post: &Post) -> Result<(), sqlx::Error> {
+    let _: Vec<Vec<Post>> = Author::load_posts(pool, authors).await?;
+    let _: Vec<Vec<Post>> = Author::load_edited_posts(pool, authors).await?;
+    let _: Vec<Vec<Post>> = Author::load_reviewed_posts(pool, authors).await?;
+    let _: Vec<Post> = authors[0].posts(pool).await?;
+    let _: Author = post.author(pool).await?;
+    let _: Option<Author> = post.editor(pool).await?;
+    Ok(())
+}
+
+fn main() {
+    let _ = load;
+}
@@ -0,0 +1,32 @@
+use shl_sqlx::Table;
+use shl_sqlx::postgres::{Readable, SoftDeletable, TableMeta};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table)]
+#[table(soft_delete = "deleted_at")]
+pub struct User {
+    pub id: i64,
+    pub deleted_at: Option<String>,
+}
+
+fn main() {
+    assert_eq!(User::SOFT_DELETE_COL, Some(r#""deleted_at""#));
+    assert_eq!(
+        User::SQL_SELECT_BY_PK,
+        r#"SELECT "id", "deleted_at" FROM "public"."users" WHERE "id" = $1 AND "deleted_at" IS NULL"#
+    );
+    assert_eq!(
+        User::SQL_DELETE_BY_PK,
+        r#"UPDATE "public"."users" SET "deleted_at" = now() WHERE "id" = $1 AND "deleted_at" IS NULL"#
+    );
+    assert_eq!(
+        User::SQL_RESTORE_BY_PK,
+        r#"UPDATE "public"."users" SET "deleted_at" = NULL WHERE "id" = $1 AND "deleted_at" IS NOT NULL"#
+    );
+    assert_eq!(
+        User::SQL_SELECT_BY_PKS,
+        r#"SELECT "id", "deleted_at" FROM "public"."users" WHERE "id" = ANY($1) AND "deleted_at" IS NULL"#
+    );
+    assert_eq!(User::SQL_COUNT, r#"SELECT count(*) FROM "public"."users" WHERE "deleted_at" IS NULL"#);
+    assert_eq!(User::SQL_PURGE_BY_PK, r#"DELETE FROM "public"."users" WHERE "id" = $1"#);
+}
@@ -0,0 +1,45 @@
+use shl_sqlx::postgres::{TableMeta, TenantBulkInsertable, TenantInsertable, TenantReadable, TenantSoftDeletable, TenantUpdatable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table, Insertable, Updatable)]
+#[table(tenant = "tenant_id", soft_delete = "deleted_at", skip_update("id"), bulk)]
+pub struct Note {
+    pub id: i64,
+    pub tenant_id: i64,
+    pub body: String,
+    pub deleted_at: Option<String>,
+}
+
+fn main() {
+    assert_eq!(Note::TENANT_COL, Some(r#""tenant_id""#));
+    assert_eq!(
+        Note::SQL_SELECT_BY_PK,
+        r#"SELECT "id", "tenant_id", "body", "deleted_at" FROM "public"."notes" WHERE "id" = $1 AND "tenant_id" = $2 AND "deleted_at" IS NULL"#
+    );
+    assert_eq!(
+        Note::SQL_SELECT_BY_PKS,
+        r#"SELECT "id", "tenant_id", "body", "deleted_at" FROM "public"."notes" WHERE "id" = ANY($1) AND "tenant_id" = $2 AND "deleted_at" IS NULL"#
+    );
+    assert_eq!(
+        Note::SQL_COUNT,
+        r#"SELECT count(*) FROM "public"."notes" WHERE "tenant_id" = $1 AND "deleted_at" IS NULL"#
+    );
+    assert_eq!(Note::SQL_PURGE_BY_PK, r#"DELETE FROM "public"."notes" WHERE "id" = $1 AND "tenant_id" = $2"#);
+    assert_eq!(
+        Note::sql_insert_many(),
+        r#"INSERT INTO "public"."notes" ("id", "tenant_id", "body", "deleted_at") SELECT "id", $4, "body", "deleted_at" FROM UNNEST($1::INT8[], $2::TEXT[], $3::TEXT[]) AS "__rows"("id", "body", "deleted_at")"#
+    );
+    assert_eq!(
+        Note::SQL_UPSERT,
+        r#"INSERT INTO "public"."notes" ("id", "tenant_id", "body", "deleted_at" ) VALUES ($1, $2, $3, $4) ON CONFLICT ("id") DO UPDATE SET "body" = EXCLUDED."body", "deleted_at" = EXCLUDED."deleted_at" WHERE "public"."notes"."tenant_id" = EXCLUDED."tenant_id""#
+    );
+    assert_eq!(
+        Note::SQL_UPDATE,
+        r#"UPDATE "public"."notes" SET "body" = $1, "deleted_at" = $2 WHERE "id" = $3 AND "tenant_id" = $4"#
+    );
+    let _ = NotePatch {
+        body: Some("hello".to_owned()),
+        ..Default::default()
+    };
+}
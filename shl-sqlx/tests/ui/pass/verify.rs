@@ -0,0 +1,22 @@
+use shl_sqlx::Table;
+use sqlx::FromRow;
+
+#[derive(sqlx::Type)]
+#[sqlx(type_name = "account_kind")]
+pub enum AccountKind {
+    Personal,
+}
+
+#[derive(FromRow, Table)]
+#[table(verify)]
+pub struct Account {
+    pub id: i64,
+    #[table(rename = "mail")]
+    #[sqlx(rename = "mail")]
+    pub email: Option<String>,
+    pub score: Option<i32>,
+    pub tags: Vec<String>,
+    pub kind: AccountKind,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use shl_sqlx::postgres::{Updatable, Upsertable};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::FromRow;
+
+#[derive(FromRow, Table, Insertable, Updatable)]
+#[table(version = "version")]
+pub struct Doc {
+    pub id: i64,
+    pub body: String,
+    pub version: i32,
+}
+
+fn main() {
+    assert_eq!(
+        Doc::SQL_UPDATE,
+        r#"UPDATE "public"."docs" SET "body" = $1, "version" = "version" + 1 WHERE "id" = $2 AND "version" = $3"#
+    );
+    assert_eq!(
+        Doc::SQL_UPSERT,
+        r#"INSERT INTO "public"."docs" ("id", "body", "version" ) VALUES ($1, $2, $3) ON CONFLICT ("id") DO UPDATE SET "body" = EXCLUDED."body", "version" = "public"."docs"."version" + 1"#
+    );
+    let _: fn(shl_sqlx::postgres::UpdateError) -> <Doc as Updatable>::Error = |e| e;
+    let _: fn(i32) -> <Doc as Updatable>::Version = |v| v;
+    assert!(DocPatch::default().is_empty());
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use shl_sqlx::postgres::{Insertable, Readable, Upsertable};
+use shl_sqlx::{Insertable, Table};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "upsert_test_settings", version = "version")]
+pub struct Setting {
+    pub id: String,
+    pub value: String,
+    pub version: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable)]
+#[table(table = "upsert_test_integrations", on_conflict("kind", "external_id"), conflict_update("name"))]
+pub struct Integration {
+    pub id: i64,
+    pub kind: String,
+    pub external_id: String,
+    pub name: String,
+    pub note: Option<String>,
+}
+
+fn setting(value: &str) -> Setting {
+    Setting {
+        id: "theme".to_owned(),
+        value: value.to_owned(),
+        version: 0,
+    }
+}
+
+fn integration(id: i64, name: &str) -> Integration {
+    Integration {
+        id,
+        kind: "google".to_owned(),
+        external_id: "42".to_owned(),
+        name: name.to_owned(),
+        note: Some(format!("note {}", id)),
+    }
+}
+
+#[test]
+fn test_sql() {
+    assert_eq!(Setting::CONFLICT_COLS, [r#""id""#]);
+    assert_eq!(
+        Setting::SQL_UPSERT,
+        r#"INSERT INTO "public"."upsert_test_settings" ("id", "value", "version" ) VALUES ($1, $2, $3) ON CONFLICT ("id") DO UPDATE SET "value" = EXCLUDED."value", "version" = "public"."upsert_test_settings"."version" + 1"#
+    );
+    assert_eq!(Integration::CONFLICT_UPDATE_COLS, [r#""name""#]);
+    assert_eq!(
+        Integration::SQL_INSERT_OR_IGNORE,
+        r#"INSERT INTO "public"."upsert_test_integrations" ("id", "kind", "external_id", "name", "note" ) VALUES ($1, $2, $3, $4, $5) ON CONFLICT ("kind", "external_id") DO NOTHING"#
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_primary_key_conflict() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS upsert_test_settings",
+        "CREATE TABLE upsert_test_settings (id TEXT PRIMARY KEY, value TEXT NOT NULL, version INT NOT NULL)",
+    ])
+    .await;
+
+    assert_eq!(setting("dark").upsert(&pool).await.unwrap(), 1);
+    assert_eq!(Setting::find_by_id(&pool, "theme".to_owned()).await.unwrap(), setting("dark"));
+
+    // the stored version is bumped, not overwritten with the one passed in
+    assert_eq!(setting("light").upsert(&pool).await.unwrap(), 1);
+    let stored = Setting::find_by_id(&pool, "theme".to_owned()).await.unwrap();
+    assert_eq!((stored.value.as_str(), stored.version), ("light", 1));
+
+    assert_eq!(setting("blue").insert_or_ignore(&pool).await.unwrap(), 0);
+    assert_eq!(Setting::find_by_id(&pool, "theme".to_owned()).await.unwrap(), stored);
+    assert!(setting("blue").insert(&pool).await.is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_unique_conflict() {
+    let pool = common::setup(&[
+        "DROP TABLE IF EXISTS upsert_test_integrations",
+        "CREATE TABLE upsert_test_integrations (id BIGINT PRIMARY KEY, kind TEXT NOT NULL, external_id TEXT NOT NULL, \
+         name TEXT NOT NULL, note TEXT, UNIQUE (kind, external_id))",
+    ])
+    .await;
+
+    assert_eq!(integration(1, "a").insert_or_ignore(&pool).await.unwrap(), 1);
+    assert_eq!(integration(2, "b").insert_or_ignore(&pool).await.unwrap(), 0);
+    assert_eq!(Integration::find_by_id(&pool, 1).await.unwrap(), integration(1, "a"));
+
+    // only conflict_update columns are overwritten, the existing key and note stay
+    assert_eq!(integration(2, "c").upsert(&pool).await.unwrap(), 1);
+    let stored = Integration::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!((stored.name.as_str(), stored.note.as_deref()), ("c", Some("note 1")));
+    assert!(!Integration::exists_by_id(&pool, 2).await.unwrap());
+    assert_eq!(Integration::count(&pool).await.unwrap(), 1);
+}
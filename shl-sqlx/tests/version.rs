@@ -0,0 +1,111 @@
+mod common;
+
+use shl_sqlx::postgres::{Insertable, Readable, Updatable, UpdateError};
+use shl_sqlx::{Insertable, Table, Updatable};
+use sqlx::{FromRow, PgPool};
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "version_test_patched", version = "version", skip_update("id"))]
+pub struct Patched {
+    pub id: i64,
+    pub body: String,
+    pub version: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromRow, Table, Insertable, Updatable)]
+#[table(table = "version_test_documents", version = "version", skip_update("id"))]
+pub struct Document {
+    pub id: i64,
+    pub body: String,
+    pub version: i32,
+}
+
+async fn setup(table: &str) -> PgPool {
+    common::setup(&[
+        &format!("DROP TABLE IF EXISTS {table}"),
+        &format!("CREATE TABLE {table} (id BIGINT PRIMARY KEY, body TEXT NOT NULL, version INT NOT NULL)"),
+    ])
+    .await
+}
+
+#[test]
+fn test_sql() {
+    assert_eq!(
+        Document::SQL_UPDATE,
+        r#"UPDATE "public"."version_test_documents" SET "body" = $1, "version" = "version" + 1 WHERE "id" = $2 AND "version" = $3"#
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_update() {
+    let pool = setup("version_test_documents").await;
+    let doc = Document {
+        id: 1,
+        body: "a".to_owned(),
+        version: 0,
+    };
+    doc.insert(&pool).await.unwrap();
+
+    // two writers read version 0; the second one loses
+    let first = Document {
+        body: "b".to_owned(),
+        ..doc.clone()
+    };
+    let second = Document {
+        body: "c".to_owned(),
+        ..doc.clone()
+    };
+    assert_eq!(first.update(&pool).await.unwrap(), 1);
+    let err = second.update(&pool).await.unwrap_err();
+    assert!(matches!(err, UpdateError::StaleVersion(ref e) if e.version == 0), "{err:?}");
+    assert!(matches!(second.update_returning(&pool).await, Err(UpdateError::StaleVersion(_))));
+
+    let stored = Document::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!((stored.body.as_str(), stored.version), ("b", 1));
+    let updated = Document {
+        body: "d".to_owned(),
+        ..stored
+    }
+    .update_returning(&pool)
+    .await
+    .unwrap();
+    assert_eq!((updated.body.as_str(), updated.version), ("d", 2));
+
+    // a deleted row is reported the same way
+    Document::delete_by_id(&pool, 1).await.unwrap();
+    assert!(matches!(updated.update(&pool).await, Err(UpdateError::StaleVersion(_))));
+}
+
+#[tokio::test]
+#[ignore = "requires DATABASE_URL"]
+async fn test_update_patch() {
+    let pool = setup("version_test_patched").await;
+    let row = Patched {
+        id: 1,
+        body: "a".to_owned(),
+        version: 0,
+    };
+    row.insert(&pool).await.unwrap();
+
+    let patch = || PatchedPatch { body: Some("b".to_owned()) };
+    assert_eq!(Patched::update_patch(&pool, 1, 0, patch()).await.unwrap(), 1);
+    let stored = Patched::find_by_id(&pool, 1).await.unwrap();
+    assert_eq!((stored.body.as_str(), stored.version), ("b", 1));
+
+    // the old version no longer matches and nothing is written
+    let err = Patched::update_patch(&pool, 1, 0, PatchedPatch { body: Some("c".to_owned()) })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, UpdateError::StaleVersion(ref e) if e.version == 0), "{err:?}");
+    assert_eq!(Patched::find_by_id(&pool, 1).await.unwrap(), stored);
+    assert!(matches!(
+        Patched::update_patch(&pool, 9, 0, patch()).await,
+        Err(UpdateError::StaleVersion(_))
+    ));
+
+    // an empty patch is not sent at all
+    assert!(PatchedPatch::default().is_empty());
+    assert_eq!(Patched::update_patch(&pool, 1, 0, PatchedPatch::default()).await.unwrap(), 0);
+    assert_eq!(Patched::find_by_id(&pool, 1).await.unwrap().version, 1);
+}